@@ -0,0 +1,152 @@
+//! Things usefull for analysing how LZW dictionary grows on given data
+//! and how much every numbers encoding pays for its codes.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::bits::get_usize_bit_len;
+use crate::lzw::lzw_decoder::LzwDecoder;
+use crate::lzw::lzw_encoder::LzwEncoder;
+use crate::number_encoders::CodeLength;
+
+/// Codes emitted while dictionary size had the same bit length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Epoch {
+    /// Bit length of dictionary size during epoch.
+    pub dictionary_bits: usize,
+    pub codes_count: usize,
+    pub average_code: f64,
+}
+
+/// Results of LZW analysis of data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    /// Codes emitted by LZW.
+    pub codes: Vec<usize>,
+    /// Number of emitted phrases of every length.
+    pub phrase_lengths: BTreeMap<usize, usize>,
+    /// Codes emitted per dictionary size epoch, in order.
+    pub epochs: Vec<Epoch>,
+    /// Most frequently emitted phrases with their counts,
+    /// from the most frequent one.
+    pub top_phrases: Vec<(Vec<u8>, usize)>,
+}
+
+/// Encodes `data` with LZW and analyses emitted phrases. Up to `top`
+/// most frequent phrases are kept.
+pub fn analyze(data: &[u8], top: usize) -> Analysis {
+    let codes = LzwEncoder::new().encode_text(data);
+    let words = LzwDecoder::new().decode_words(&codes);
+
+    let mut phrase_lengths = BTreeMap::new();
+    let mut phrase_counts: HashMap<&[u8], usize> = HashMap::new();
+
+    for word in &words {
+        *phrase_lengths.entry(word.len()).or_default() += 1;
+        *phrase_counts.entry(word.get_symbols_ref()).or_default() += 1;
+    }
+
+    let mut top_phrases: Vec<_> = phrase_counts
+        .into_iter()
+        .map(|(phrase, count)| (phrase.to_vec(), count))
+        .collect();
+
+    top_phrases.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    top_phrases.truncate(top);
+
+    Analysis {
+        epochs: epochs(&codes),
+        codes,
+        phrase_lengths,
+        top_phrases,
+    }
+}
+
+/// Splits `codes` into epochs. Dictionary grows by one word with
+/// every code, so its size is known from code position.
+fn epochs(codes: &[usize]) -> Vec<Epoch> {
+    let mut epochs: Vec<Epoch> = vec![];
+    let mut codes_sum = 0;
+
+    for (i, &code) in codes.iter().enumerate() {
+        let dictionary_size = crate::initial_dictionary_size() + i;
+        let dictionary_bits = get_usize_bit_len(dictionary_size);
+
+        if epochs.last().map(|epoch| epoch.dictionary_bits) != Some(dictionary_bits) {
+            codes_sum = 0;
+            epochs.push(Epoch {
+                dictionary_bits,
+                codes_count: 0,
+                average_code: 0.0,
+            });
+        }
+
+        let epoch = epochs.last_mut().unwrap();
+        epoch.codes_count += 1;
+        codes_sum += code;
+        epoch.average_code = codes_sum as f64 / epoch.codes_count as f64;
+    }
+
+    epochs
+}
+
+/// Counts codes of every length in bits when `codes` are encoded with `E`.
+pub fn code_len_histogram<E>(codes: &[usize]) -> BTreeMap<usize, usize>
+where
+    E: CodeLength,
+{
+    let mut histogram = BTreeMap::new();
+
+    for &code in codes {
+        // Needed because elias can't handle 0.
+        *histogram.entry(E::code_len(code + 1)).or_default() += 1;
+    }
+
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EliasGammaEncoder, FibbonaciEncoder};
+
+    #[test]
+    fn analyze_works() {
+        let analysis = analyze(b"abababab", 2);
+
+        // Phrases: a, b, ab, aba, b.
+        assert_eq!(vec![97, 98, 256, 258, 98], analysis.codes);
+        assert_eq!(
+            BTreeMap::from([(1, 3), (2, 1), (3, 1)]),
+            analysis.phrase_lengths
+        );
+        assert_eq!(
+            vec![(b"b".to_vec(), 2), (b"a".to_vec(), 1)],
+            analysis.top_phrases
+        );
+    }
+
+    #[test]
+    fn epochs_works() {
+        let codes: Vec<_> = (0..300).collect();
+        let epochs = epochs(&codes);
+
+        assert_eq!(2, epochs.len());
+        assert_eq!((9, 256), (epochs[0].dictionary_bits, epochs[0].codes_count));
+        assert_eq!(127.5, epochs[0].average_code);
+        assert_eq!((10, 44), (epochs[1].dictionary_bits, epochs[1].codes_count));
+    }
+
+    #[test]
+    fn code_len_histogram_works() {
+        let codes = [0, 1, 2, 3, 6];
+
+        assert_eq!(
+            BTreeMap::from([(1, 1), (3, 2), (5, 2)]),
+            code_len_histogram::<EliasGammaEncoder>(&codes)
+        );
+        assert_eq!(
+            BTreeMap::from([(2, 1), (3, 1), (4, 2), (5, 1)]),
+            code_len_histogram::<FibbonaciEncoder>(&codes)
+        );
+    }
+}
@@ -0,0 +1,506 @@
+//! Things usefull for packing many files into one compressed archive.
+//!
+//! Archive starts with magic bytes. Every file is compressed separately
+//! with LZW and registered codec, so one member can be extracted without
+//! decoding the others. Directory of members is written at the end
+//! of archive, followed by codec tag, directory length and members count.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::bits::Bits;
+use crate::io_utils::{self, invalid_data, read_usize, USIZE_LEN};
+use crate::lzw::lzw_encoder::LzwEncoder;
+use crate::number_encoders::{Codec, CODECS};
+
+/// Bytes every archive starts with.
+pub const MAGIC: [u8; 4] = *b"UCAR";
+
+/// Codec tag, directory length and members count ending archive.
+const TRAILER_LEN: usize = 3 * USIZE_LEN;
+
+/// Path length and six numbers of entry without its path.
+const MIN_ENTRY_LEN: usize = 7 * USIZE_LEN;
+
+/// Metadata of file or directory stored in archive.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Entry {
+    pub path: String,
+    pub is_dir: bool,
+    pub size: usize,
+    pub mode: u32,
+    pub mtime: u64,
+    offset: usize,
+    bits_len: usize,
+}
+
+impl Entry {
+    fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.path.len().to_be_bytes());
+        bytes.extend_from_slice(self.path.as_bytes());
+
+        for number in [
+            self.is_dir as usize,
+            self.size,
+            self.mode as usize,
+            self.mtime as usize,
+            self.offset,
+            self.bits_len,
+        ] {
+            bytes.extend_from_slice(&number.to_be_bytes());
+        }
+    }
+
+    fn read(bytes: &mut &[u8]) -> io::Result<Entry> {
+        let path_len = read_usize(bytes)?;
+        let path = io_utils::take(bytes, path_len)?;
+
+        Ok(Entry {
+            path: String::from_utf8_lossy(path).into_owned(),
+            is_dir: read_usize(bytes)? != 0,
+            size: read_usize(bytes)?,
+            mode: read_usize(bytes)? as u32,
+            mtime: read_usize(bytes)? as u64,
+            offset: read_usize(bytes)?,
+            bits_len: read_usize(bytes)?,
+        })
+    }
+}
+
+/// Used to write archive member by member.
+pub struct ArchiveWriter<W> {
+    writer: W,
+    codec_tag: usize,
+    entries: Vec<Entry>,
+    offset: usize,
+}
+
+impl<W> ArchiveWriter<W>
+where
+    W: Write,
+{
+    /// Creates new instance of `ArchiveWriter` compressing members
+    /// with codec at `codec_tag` position of `CODECS`.
+    pub fn new(mut writer: W, codec_tag: usize) -> io::Result<ArchiveWriter<W>> {
+        writer.write_all(&MAGIC)?;
+
+        Ok(ArchiveWriter {
+            writer,
+            codec_tag,
+            entries: vec![],
+            offset: MAGIC.len(),
+        })
+    }
+
+    /// Adds file or whole directory tree at `path`. Members are named
+    /// after `path` without root and `.` components, like in tar,
+    /// paths with `..` components are refused. Symbolic links inside
+    /// directories aren't followed nor stored, so link loops can't make
+    /// walking directory tree endless, symbolic link `path` is refused.
+    pub fn add_path(&mut self, path: &Path) -> io::Result<()> {
+        if fs::symlink_metadata(path)?.file_type().is_symlink() {
+            return Err(invalid_input(&format!("{} is a symbolic link", path.display())));
+        }
+
+        self.add_tree(path)
+    }
+
+    fn add_tree(&mut self, path: &Path) -> io::Result<()> {
+        let metadata = fs::symlink_metadata(path)?;
+
+        if metadata.file_type().is_symlink() {
+            return Ok(());
+        }
+
+        let mut entry = Entry {
+            path: archive_path(path)?,
+            is_dir: metadata.is_dir(),
+            size: 0,
+            mode: file_mode(&metadata),
+            mtime: metadata
+                .modified()?
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            offset: self.offset,
+            bits_len: 0,
+        };
+
+        if metadata.is_dir() {
+            self.entries.push(entry);
+
+            let mut children: Vec<_> = fs::read_dir(path)?
+                .map(|child| child.map(|c| c.path()))
+                .collect::<io::Result<_>>()?;
+            children.sort();
+
+            for child in children {
+                self.add_tree(&child)?;
+            }
+        } else {
+            let data = fs::read(path)?;
+            let codec = &CODECS[self.codec_tag];
+
+            // Codes are shifted by one, so they can be equal to the bound.
+            if LzwEncoder::new().code_bound(data.len()) > codec.max_number {
+                let message = format!("{} is too big for {} codec", path.display(), codec.name);
+
+                return Err(invalid_input(&message));
+            }
+
+            let bits = crate::encode_with(&data, codec.encode);
+
+            self.writer.write_all(bits.get_bits())?;
+            self.offset += bits.get_bits().len();
+
+            entry.size = data.len();
+            entry.bits_len = bits.len();
+            self.entries.push(entry);
+        }
+
+        Ok(())
+    }
+
+    /// Writes members directory and returns inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut directory = vec![];
+
+        for entry in &self.entries {
+            entry.write(&mut directory);
+        }
+
+        for number in [self.codec_tag, directory.len(), self.entries.len()] {
+            directory.extend_from_slice(&number.to_be_bytes());
+        }
+
+        self.writer.write_all(&directory)?;
+
+        Ok(self.writer)
+    }
+}
+
+/// Used to list and extract members of archive.
+pub struct Archive<R> {
+    reader: R,
+    codec: &'static Codec,
+    entries: Vec<Entry>,
+}
+
+impl<R> Archive<R>
+where
+    R: Read + Seek,
+{
+    /// Opens archive reading only its members directory.
+    pub fn new(mut reader: R) -> io::Result<Archive<R>> {
+        let archive_len = reader.seek(SeekFrom::End(0))?;
+
+        let mut magic = [0; MAGIC.len()];
+        reader.seek(SeekFrom::Start(0))?;
+
+        if archive_len < (MAGIC.len() + TRAILER_LEN) as u64
+            || reader.read_exact(&mut magic).is_err()
+            || magic != MAGIC
+        {
+            return Err(invalid_data("file isn't an archive"));
+        }
+
+        let mut trailer = [0; TRAILER_LEN];
+        reader.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+        reader.read_exact(&mut trailer)?;
+
+        let mut trailer = &trailer[..];
+        let codec_tag = read_usize(&mut trailer)?;
+        let directory_len = read_usize(&mut trailer)?;
+        let entries_count = read_usize(&mut trailer)?;
+
+        let codec = CODECS
+            .get(codec_tag)
+            .ok_or_else(|| invalid_data("unknown codec tag"))?;
+
+        // Directory has to fit between magic and trailer and hold all entries.
+        let directory_start = (archive_len - TRAILER_LEN as u64)
+            .checked_sub(directory_len as u64)
+            .filter(|&start| start >= MAGIC.len() as u64)
+            .ok_or_else(|| invalid_data("archive directory is corrupted"))?;
+
+        if entries_count > directory_len / MIN_ENTRY_LEN {
+            return Err(invalid_data("archive directory is corrupted"));
+        }
+
+        let mut directory = vec![0; directory_len];
+        reader.seek(SeekFrom::Start(directory_start))?;
+        reader.read_exact(&mut directory)?;
+
+        let mut directory = &directory[..];
+        let entries: Vec<_> = (0..entries_count)
+            .map(|_| Entry::read(&mut directory))
+            .collect::<io::Result<_>>()?;
+
+        // Data of every member lies between magic and directory.
+        let data_fits = entries.iter().all(|entry| {
+            entry.offset >= MAGIC.len()
+                && (entry.offset as u64)
+                    .checked_add(entry.bits_len.div_ceil(8) as u64)
+                    .is_some_and(|end| end <= directory_start)
+        });
+
+        if !data_fits {
+            return Err(invalid_data("archive directory is corrupted"));
+        }
+
+        Ok(Archive {
+            reader,
+            codec,
+            entries,
+        })
+    }
+
+    /// Returns members of archive in order they were added.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Returns name of codec used by archive members.
+    pub fn codec_name(&self) -> &'static str {
+        self.codec.name
+    }
+
+    /// Reads and decodes only data of `entry`.
+    pub fn read_entry(&mut self, entry: &Entry) -> io::Result<Vec<u8>> {
+        if entry.is_dir {
+            return Ok(vec![]);
+        }
+
+        let mut bytes = vec![0; entry.bits_len.div_ceil(8)];
+        self.reader.seek(SeekFrom::Start(entry.offset as u64))?;
+        self.reader.read_exact(&mut bytes)?;
+
+        let bits = Bits::from_vec(entry.bits_len, bytes);
+
+        Ok(crate::decode_with(&bits, self.codec.decode))
+    }
+
+    /// Extracts members named in `paths` (or all of them if `paths` is empty)
+    /// into `destination`, restoring their permissions and modification times.
+    pub fn extract(&mut self, destination: &Path, paths: &[String]) -> io::Result<()> {
+        let entries: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|e| paths.is_empty() || paths.contains(&e.path))
+            .cloned()
+            .collect();
+
+        for entry in &entries {
+            let path = destination.join(safe_path(&entry.path)?);
+
+            if entry.is_dir {
+                fs::create_dir_all(&path)?;
+            } else {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+
+                fs::write(&path, self.read_entry(entry)?)?;
+                restore_metadata(&path, entry)?;
+            }
+        }
+
+        // Directories are finished after their content was written.
+        for entry in entries.iter().filter(|e| e.is_dir).rev() {
+            restore_metadata(&destination.join(safe_path(&entry.path)?), entry)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates member name from `path` using `/` as separator. Only normal
+/// components are kept, so members can always be extracted. Paths with
+/// `..` are refused, dropping it would change meaning of the path.
+fn archive_path(path: &Path) -> io::Result<String> {
+    if path.components().any(|c| c == Component::ParentDir) {
+        let message = format!("{} contains '..' component", path.display());
+
+        return Err(invalid_input(&message));
+    }
+
+    let name = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    Ok(name)
+}
+
+/// Checks that member name doesn't point outside of extraction directory.
+fn safe_path(name: &str) -> io::Result<PathBuf> {
+    let path = PathBuf::from(name);
+
+    match path.components().all(|c| matches!(c, Component::Normal(_))) {
+        true => Ok(path),
+        false => Err(invalid_data("member path leaves extraction directory")),
+    }
+}
+
+fn restore_metadata(path: &Path, entry: &Entry) -> io::Result<()> {
+    File::open(path)?.set_modified(UNIX_EPOCH + Duration::from_secs(entry.mtime))?;
+
+    set_file_mode(path, entry.mode)
+}
+
+#[cfg(unix)]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    match metadata.permissions().readonly() {
+        true => 0o444,
+        false => 0o644,
+    }
+}
+
+#[cfg(unix)]
+fn set_file_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_file_mode(path: &Path, mode: u32) -> io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(mode & 0o200 == 0);
+
+    fs::set_permissions(path, permissions)
+}
+
+fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn archive_path_works() {
+        assert_eq!("a/b.txt", archive_path(Path::new("/a/./b.txt")).unwrap());
+        assert!(archive_path(Path::new("../c")).is_err());
+        assert!(archive_path(Path::new("a/../c")).is_err());
+    }
+
+    #[test]
+    fn safe_path_works() {
+        assert!(safe_path("a/b.txt").is_ok());
+        assert!(safe_path("../c").is_err());
+    }
+
+    #[test]
+    fn archive_roundtrip_works() {
+        let root = std::env::temp_dir().join(format!("uc_archive_{}", std::process::id()));
+        let source = root.join("source");
+        fs::create_dir_all(source.join("nested")).unwrap();
+        fs::write(source.join("a.txt"), b"abracadabra abracadabra").unwrap();
+        fs::write(source.join("nested/b.txt"), b"").unwrap();
+
+        let mut writer = ArchiveWriter::new(vec![], 0).unwrap();
+        writer.add_path(&source).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let mut archive = Archive::new(Cursor::new(bytes)).unwrap();
+        let source_name = archive_path(&source).unwrap();
+
+        let paths: Vec<_> = archive.entries().iter().map(|e| e.path.clone()).collect();
+        let member = format!("{}/a.txt", source_name);
+
+        assert_eq!(
+            vec![
+                source_name.clone(),
+                member.clone(),
+                format!("{}/nested", source_name),
+                format!("{}/nested/b.txt", source_name),
+            ],
+            paths
+        );
+
+        let destination = root.join("destination");
+        archive.extract(&destination, std::slice::from_ref(&member)).unwrap();
+
+        assert_eq!(
+            b"abracadabra abracadabra".to_vec(),
+            fs::read(destination.join(&member)).unwrap()
+        );
+        assert!(!destination.join(format!("{}/nested", source_name)).exists());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_skipped() {
+        let root = std::env::temp_dir().join(format!("uc_archive_links_{}", std::process::id()));
+        fs::create_dir_all(root.join("dir")).unwrap();
+        fs::write(root.join("dir/a.txt"), b"abc").unwrap();
+        std::os::unix::fs::symlink(&root, root.join("dir/loop")).unwrap();
+
+        let mut writer = ArchiveWriter::new(vec![], 0).unwrap();
+        writer.add_path(&root.join("dir")).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let archive = Archive::new(Cursor::new(bytes)).unwrap();
+        let dir_name = archive_path(&root.join("dir")).unwrap();
+
+        let paths: Vec<_> = archive.entries().iter().map(|e| e.path.clone()).collect();
+
+        assert_eq!(vec![dir_name.clone(), format!("{}/a.txt", dir_name)], paths);
+
+        let mut writer = ArchiveWriter::new(vec![], 0).unwrap();
+        let error = writer.add_path(&root.join("dir/loop")).unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidInput, error.kind());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn corrupted_archive_is_rejected() {
+        let mut writer = ArchiveWriter::new(vec![], 0).unwrap();
+        writer.add_path(Path::new("src/archive.rs")).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let directory_len_start = bytes.len() - 2 * USIZE_LEN;
+        let entries_count_start = bytes.len() - USIZE_LEN;
+
+        let corruptions = [
+            (0, usize::from_be_bytes(*b"NOTUCAR!")),
+            (directory_len_start, usize::MAX),
+            (directory_len_start, 1 << 40),
+            (entries_count_start, usize::MAX),
+            (entries_count_start, 2),
+        ];
+
+        for (start, number) in corruptions {
+            let mut corrupted = bytes.clone();
+            corrupted[start..start + USIZE_LEN].copy_from_slice(&number.to_be_bytes());
+
+            let error = Archive::new(Cursor::new(corrupted)).err().unwrap();
+
+            assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        }
+
+        let error = Archive::new(Cursor::new(&bytes[..10])).err().unwrap();
+
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+    }
+}
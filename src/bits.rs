@@ -1,4 +1,6 @@
-const USIZE_HALF: usize = std::usize::MAX - (std::usize::MAX >> 1);
+pub mod rank_select;
+
+const USIZE_HALF: usize = usize::MAX - (usize::MAX >> 1);
 
 #[derive(Debug)]
 pub enum Bit {
@@ -6,7 +8,7 @@ pub enum Bit {
     ZERO,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct Bits {
     bytes: Vec<u8>,
     size: usize,
@@ -24,7 +26,7 @@ impl Bits {
         Bits { size, bytes }
     }
 
-    pub fn iter(&self) -> BitsIterator {
+    pub fn iter(&self) -> BitsIterator<'_> {
         BitsIterator {
             bits: self,
             index: 0,
@@ -54,6 +56,38 @@ impl Bits {
         }
     }
 
+    /// Returns copy of bits from `start` up to `end` (exclusive).
+    pub fn slice(&self, start: usize, end: usize) -> Bits {
+        let mut bits = Bits::new();
+
+        for i in start..end {
+            bits.push_bit(self.get_bit(i));
+        }
+
+        bits
+    }
+
+    /// Pushes `width` least significant bits of `number`,
+    /// starting from the most significant one.
+    pub fn push_number(&mut self, number: usize, width: usize) {
+        for i in (0..width).rev() {
+            if number & 1 << i > 0 {
+                self.push_bit(Bit::ONE);
+            } else {
+                self.push_bit(Bit::ZERO);
+            }
+        }
+    }
+
+    /// Reads `width` bits starting at `start` as number,
+    /// the first one being the most significant.
+    pub fn get_number(&self, start: usize, width: usize) -> usize {
+        (start..start + width).fold(0, |number, i| match self.get_bit(i) {
+            Bit::ONE => number << 1 | 1,
+            Bit::ZERO => number << 1,
+        })
+    }
+
     pub fn get_bits(&self) -> &[u8] {
         &self.bytes
     }
@@ -68,7 +102,7 @@ impl Bits {
         self.size -= 1;
     }
 
-    fn get_bit(&self, index: usize) -> Bit {
+    pub fn get_bit(&self, index: usize) -> Bit {
         if index > self.size - 1 {
             panic!("index too big");
         }
@@ -111,6 +145,10 @@ impl Bits {
         self.size
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
     fn create_mask(bit_position: usize) -> u8 {
         if bit_position > 7 {
             panic!("too big bit position");
@@ -158,7 +196,7 @@ impl From<usize> for Bits {
             start_mask -= 1;
         }
 
-        return bits;
+        bits
     }
 }
 
@@ -188,7 +226,7 @@ pub fn get_usize_bit_len(number: usize) -> usize {
         size += 1;
     }
 
-    return size;
+    size
 }
 
 #[cfg(test)]
@@ -237,6 +275,35 @@ mod tests {
         assert_eq!([0b10101101, 0b00000000], bits.get_bits());
     }
 
+    #[test]
+    fn push_number_works() {
+        let mut bits = Bits::new();
+
+        bits.push_number(0b101, 3);
+        bits.push_number(0b1, 8);
+
+        assert_eq!(11, bits.len());
+        assert_eq!([0b10100000, 0b00100000], bits.get_bits());
+    }
+
+    #[test]
+    fn get_number_works() {
+        let bits: Bits = 0b110101101.into();
+
+        assert_eq!(0b01011, bits.get_number(2, 5));
+        assert_eq!(0, bits.get_number(4, 0));
+    }
+
+    #[test]
+    fn slice_works() {
+        let bits: Bits = 0b110101101.into();
+
+        let sliced = bits.slice(2, 7);
+
+        assert_eq!(5, sliced.len());
+        assert_eq!([0b01011000], sliced.get_bits());
+    }
+
     #[test]
     fn from_usize_works() {
         let number = 137;
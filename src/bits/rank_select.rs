@@ -0,0 +1,258 @@
+//! Rank and select queries over `Bits` answered without scanning them whole.
+
+use crate::bits::Bits;
+
+const BLOCK: usize = 512;
+const BLOCK_BYTES: usize = BLOCK / 8;
+const BLOCKS_PER_SUPERBLOCK: usize = 128;
+
+/// `Bits` with index of ones counts. Absolute counts are kept for every
+/// superblock of 65536 bits and counts relative to them for every block
+/// of 512 bits, so index takes about 3% of bits.
+#[derive(Clone)]
+pub struct RankSelect {
+    bits: Bits,
+    ones: usize,
+    superblocks: Vec<usize>,
+    blocks: Vec<u16>,
+}
+
+impl RankSelect {
+    /// Creates new instance of `RankSelect` indexing `bits`.
+    pub fn new(bits: Bits) -> RankSelect {
+        let bits = clear_padding(bits);
+        let bytes = bits.get_bits();
+
+        let mut ones = 0;
+        let mut superblocks = vec![];
+        let mut blocks = vec![];
+
+        // Block containing position right after the last bit is indexed too,
+        // so rank of every position up to length can be answered.
+        for block in 0..bits.len() / BLOCK + 1 {
+            if block % BLOCKS_PER_SUPERBLOCK == 0 {
+                superblocks.push(ones);
+            }
+
+            blocks.push((ones - superblocks[superblocks.len() - 1]) as u16);
+
+            let start = (block * BLOCK_BYTES).min(bytes.len());
+            let end = (start + BLOCK_BYTES).min(bytes.len());
+
+            ones += count_ones(&bytes[start..end]);
+        }
+
+        RankSelect {
+            bits,
+            ones,
+            superblocks,
+            blocks,
+        }
+    }
+
+    /// Returns indexed bits.
+    pub fn bits(&self) -> &Bits {
+        &self.bits
+    }
+
+    /// Returns number of indexed bits.
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Checks if there are no indexed bits.
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Returns number of ones in all bits.
+    pub fn count_ones(&self) -> usize {
+        self.ones
+    }
+
+    /// Counts ones before `index`.
+    pub fn rank1(&self, index: usize) -> usize {
+        assert!(index <= self.len(), "index too big");
+
+        let bytes = self.bits.get_bits();
+        let block = index / BLOCK;
+
+        let mut rank = self.block_rank1(block);
+        rank += count_ones(&bytes[block * BLOCK_BYTES..index / 8]);
+
+        let bit_position = index % 8;
+
+        if bit_position > 0 {
+            rank += (bytes[index / 8] >> (8 - bit_position)).count_ones() as usize;
+        }
+
+        rank
+    }
+
+    /// Counts zeros before `index`.
+    pub fn rank0(&self, index: usize) -> usize {
+        index - self.rank1(index)
+    }
+
+    /// Finds position of `rank`-th (counted from 0) one.
+    pub fn select1(&self, rank: usize) -> Option<usize> {
+        self.select(rank, true)
+    }
+
+    /// Finds position of `rank`-th (counted from 0) zero.
+    pub fn select0(&self, rank: usize) -> Option<usize> {
+        self.select(rank, false)
+    }
+
+    fn select(&self, mut rank: usize, one: bool) -> Option<usize> {
+        let block_rank = |block: usize| match one {
+            true => self.block_rank1(block),
+            false => block * BLOCK - self.block_rank1(block),
+        };
+
+        let count = match one {
+            true => self.ones,
+            false => self.len() - self.ones,
+        };
+
+        if rank >= count {
+            return None;
+        }
+
+        // Finds the last block starting with rank not greater than `rank`.
+        let (mut low, mut high) = (0, self.blocks.len());
+
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+
+            match block_rank(middle) <= rank {
+                true => low = middle,
+                false => high = middle,
+            }
+        }
+
+        rank -= block_rank(low);
+
+        let bytes = self.bits.get_bits();
+
+        for (byte_index, &byte) in bytes.iter().enumerate().skip(low * BLOCK_BYTES) {
+            let start = byte_index * 8;
+            let valid = (self.len() - start).min(8);
+
+            // Padding after the end was cleared, so ones can be counted in whole byte.
+            let byte_count = match one {
+                true => byte.count_ones() as usize,
+                false => valid - byte.count_ones() as usize,
+            };
+
+            if rank >= byte_count {
+                rank -= byte_count;
+                continue;
+            }
+
+            for position in 0..valid {
+                if (byte & 0x80 >> position != 0) == one {
+                    if rank == 0 {
+                        return Some(start + position);
+                    }
+
+                    rank -= 1;
+                }
+            }
+        }
+
+        unreachable!("ones count doesn't match bits")
+    }
+
+    fn block_rank1(&self, block: usize) -> usize {
+        self.superblocks[block / BLOCKS_PER_SUPERBLOCK] + self.blocks[block] as usize
+    }
+}
+
+impl Default for RankSelect {
+    fn default() -> Self {
+        RankSelect::new(Bits::new())
+    }
+}
+
+/// Drops bytes after the end of `bits` and zeros bits after the end in
+/// the last byte, e.g. garbage left by `Bits::from_vec`.
+fn clear_padding(bits: Bits) -> Bits {
+    let len = bits.len();
+    let mut bytes = bits.get_bits()[..len.div_ceil(8)].to_vec();
+
+    let padding = bytes.len() * 8 - len;
+
+    if let Some(last) = bytes.last_mut() {
+        *last &= 0xFF << padding;
+    }
+
+    Bits::from_vec(len, bytes)
+}
+
+fn count_ones(bytes: &[u8]) -> usize {
+    bytes.iter().map(|byte| byte.count_ones() as usize).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bits::Bit;
+
+    #[test]
+    fn rank_select_works() {
+        let rank_select = RankSelect::new(0b1001101.into());
+
+        assert_eq!(4, rank_select.count_ones());
+        assert_eq!(0, rank_select.rank1(0));
+        assert_eq!(2, rank_select.rank1(4));
+        assert_eq!(4, rank_select.rank1(7));
+        assert_eq!(3, rank_select.rank0(7));
+
+        assert_eq!(Some(0), rank_select.select1(0));
+        assert_eq!(Some(6), rank_select.select1(3));
+        assert_eq!(None, rank_select.select1(4));
+        assert_eq!(Some(5), rank_select.select0(2));
+        assert_eq!(None, rank_select.select0(3));
+    }
+
+    #[test]
+    fn many_blocks_works() {
+        let mut bits = Bits::new();
+        let positions: Vec<_> = (0..200_000).filter(|i| i % 7 == 0 || i % 13 == 0).collect();
+
+        for i in 0..200_000 {
+            match positions.binary_search(&i) {
+                Ok(_) => bits.push_bit(Bit::ONE),
+                Err(_) => bits.push_bit(Bit::ZERO),
+            }
+        }
+
+        let rank_select = RankSelect::new(bits);
+
+        for (rank, &position) in positions.iter().enumerate().step_by(97) {
+            assert_eq!(Some(position), rank_select.select1(rank));
+            assert_eq!(rank, rank_select.rank1(position));
+            assert_eq!(rank + 1, rank_select.rank1(position + 1));
+        }
+
+        // 1 and 2 are the first zeros.
+        assert_eq!(Some(2), rank_select.select0(1));
+        assert_eq!(positions.len(), rank_select.rank1(200_000));
+        assert_eq!(None, rank_select.select1(positions.len()));
+    }
+
+    #[test]
+    fn garbage_padding_is_ignored() {
+        // Only the first 5 bits are valid, the rest of bytes is garbage.
+        let rank_select = RankSelect::new(Bits::from_vec(5, vec![0b1010_0111, 0xFF]));
+
+        assert_eq!(2, rank_select.count_ones());
+        assert_eq!(2, rank_select.rank1(5));
+        assert_eq!(3, rank_select.rank0(5));
+        assert_eq!(Some(2), rank_select.select1(1));
+        assert_eq!(None, rank_select.select1(2));
+        assert_eq!(Some(4), rank_select.select0(2));
+        assert_eq!(None, rank_select.select0(3));
+    }
+}
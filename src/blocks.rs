@@ -0,0 +1,367 @@
+//! Things usefull for compressing data in independent blocks on many threads.
+
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+use crossbeam_channel::unbounded;
+
+use crate::bits::Bits;
+use crate::io_utils::{invalid_data, read_usize, USIZE_LEN};
+use crate::number_encoders::{NumberDecoder, NumberEncoder};
+
+pub const DEFAULT_BLOCK_SIZE: usize = 1 << 20;
+
+/// Data length, bits length and blocks count ending blocks index.
+const TRAILER_LEN: usize = 3 * USIZE_LEN;
+
+/// Block of data compressed with its own `LzwEncoder`.
+pub struct Block {
+    pub data_len: usize,
+    pub bits: Bits,
+}
+
+/// Splits `data` into blocks of `block_size` bytes and compresses them
+/// with LZW and `E` on `threads` worker threads. Blocks are returned in order.
+pub fn compress_blocks<E>(data: &[u8], block_size: usize, threads: usize) -> Vec<Block>
+where
+    E: NumberEncoder,
+{
+    compress_blocks_with(data, block_size, threads, E::encode)
+}
+
+/// Decompresses `blocks` on `threads` worker threads and joins them in order.
+pub fn decompress_blocks<D>(blocks: &[Block], threads: usize) -> Vec<u8>
+where
+    D: NumberDecoder,
+{
+    decompress_blocks_with(blocks, threads, D::decode)
+}
+
+/// Works like `compress_blocks`, but encoder can be chosen at runtime.
+pub fn compress_blocks_with(
+    data: &[u8],
+    block_size: usize,
+    threads: usize,
+    encode: fn(&[usize]) -> Bits,
+) -> Vec<Block> {
+    assert!(block_size > 0, "block size has to be positive");
+
+    let chunks: Vec<_> = data.chunks(block_size).collect();
+
+    run_parallel(&chunks, threads, |chunk| Block {
+        data_len: chunk.len(),
+        bits: crate::encode_with(chunk, encode),
+    })
+}
+
+/// Works like `decompress_blocks`, but decoder can be chosen at runtime.
+pub fn decompress_blocks_with(
+    blocks: &[Block],
+    threads: usize,
+    decode: fn(&Bits) -> Vec<usize>,
+) -> Vec<u8> {
+    run_parallel(blocks, threads, |block| crate::decode_with(&block.bits, decode))
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Runs `job` for every item on `threads` workers fed through channel.
+/// Results are returned in the order of `items`.
+fn run_parallel<T, R, F>(items: &[T], threads: usize, job: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let (job_sender, job_receiver) = unbounded();
+    let (result_sender, result_receiver) = unbounded();
+
+    for item in items.iter().enumerate() {
+        job_sender.send(item).expect("job receiver exists");
+    }
+
+    drop(job_sender);
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            let job_receiver = job_receiver.clone();
+            let result_sender = result_sender.clone();
+            let job = &job;
+
+            scope.spawn(move || {
+                for (index, item) in job_receiver {
+                    result_sender
+                        .send((index, job(item)))
+                        .expect("result receiver exists");
+                }
+            });
+        }
+    });
+
+    drop(result_sender);
+
+    let mut results: Vec<_> = result_receiver.into_iter().collect();
+    results.sort_unstable_by_key(|(index, _)| *index);
+
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Position of the block in uncompressed data and in compressed bits.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BlockIndexEntry {
+    pub data_offset: usize,
+    pub bit_offset: usize,
+}
+
+/// Index of blocks written at the end of blocks container,
+/// so every block can be found without reading the others.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BlockIndex {
+    pub entries: Vec<BlockIndexEntry>,
+    pub data_len: usize,
+    pub bits_len: usize,
+}
+
+impl BlockIndex {
+    /// Creates index of `blocks` laid one after another.
+    pub fn from_blocks(blocks: &[Block]) -> BlockIndex {
+        let mut entries = Vec::with_capacity(blocks.len());
+        let mut data_offset = 0;
+        let mut bit_offset = 0;
+
+        for block in blocks {
+            entries.push(BlockIndexEntry {
+                data_offset,
+                bit_offset,
+            });
+
+            data_offset += block.data_len;
+            bit_offset += block.bits.len();
+        }
+
+        BlockIndex {
+            entries,
+            data_len: data_offset,
+            bits_len: bit_offset,
+        }
+    }
+
+    /// Range of uncompressed data covered by `block`.
+    pub fn data_range(&self, block: usize) -> Range<usize> {
+        let end = self.entries.get(block + 1).map_or(self.data_len, |e| e.data_offset);
+
+        self.entries[block].data_offset..end
+    }
+
+    /// Range of compressed bits of `block`.
+    pub fn bit_range(&self, block: usize) -> Range<usize> {
+        let end = self.entries.get(block + 1).map_or(self.bits_len, |e| e.bit_offset);
+
+        self.entries[block].bit_offset..end
+    }
+
+    /// Finds block containing byte of uncompressed data at `position`.
+    pub fn find_block(&self, position: usize) -> Option<usize> {
+        if position >= self.data_len {
+            return None;
+        }
+
+        Some(self.entries.partition_point(|e| e.data_offset <= position) - 1)
+    }
+
+    /// Writes every entry followed by data length, bits length and blocks count.
+    fn write(&self, bytes: &mut Vec<u8>) {
+        for entry in &self.entries {
+            bytes.extend_from_slice(&entry.data_offset.to_be_bytes());
+            bytes.extend_from_slice(&entry.bit_offset.to_be_bytes());
+        }
+
+        bytes.extend_from_slice(&self.data_len.to_be_bytes());
+        bytes.extend_from_slice(&self.bits_len.to_be_bytes());
+        bytes.extend_from_slice(&self.entries.len().to_be_bytes());
+    }
+
+    /// Reads index from the end of blocks container, which starts
+    /// at current position of `reader`.
+    pub fn read<R>(reader: &mut R) -> io::Result<BlockIndex>
+    where
+        R: Read + Seek,
+    {
+        let start = reader.stream_position()?;
+        let container_len = reader
+            .seek(SeekFrom::End(0))?
+            .checked_sub(start)
+            .ok_or_else(|| invalid_data("blocks container is truncated"))?;
+
+        if container_len < TRAILER_LEN as u64 {
+            return Err(invalid_data("blocks container is truncated"));
+        }
+
+        let mut trailer = [0; TRAILER_LEN];
+        reader.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+        reader.read_exact(&mut trailer)?;
+
+        let mut trailer = &trailer[..];
+        let data_len = read_usize(&mut trailer)?;
+        let bits_len = read_usize(&mut trailer)?;
+        let blocks_count = read_usize(&mut trailer)?;
+
+        // Index and bits of blocks have to fit in the container.
+        let entries_len = blocks_count
+            .checked_mul(2 * USIZE_LEN)
+            .filter(|&len| len as u64 <= container_len - TRAILER_LEN as u64)
+            .ok_or_else(|| invalid_data("blocks index is corrupted"))?;
+        let index_len = (entries_len + TRAILER_LEN) as u64;
+
+        if bits_len.div_ceil(8) as u64 > container_len - index_len {
+            return Err(invalid_data("blocks index is corrupted"));
+        }
+
+        let mut entries = vec![0; entries_len];
+        reader.seek(SeekFrom::End(-(index_len as i64)))?;
+        reader.read_exact(&mut entries)?;
+
+        let mut entries = &entries[..];
+
+        let entries = (0..blocks_count)
+            .map(|_| {
+                Ok(BlockIndexEntry {
+                    data_offset: read_usize(&mut entries)?,
+                    bit_offset: read_usize(&mut entries)?,
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        // Offsets can't decrease nor pass the end, so ranges of blocks are valid.
+        let end = BlockIndexEntry {
+            data_offset: data_len,
+            bit_offset: bits_len,
+        };
+        let sorted = entries
+            .iter()
+            .zip(entries.iter().skip(1).chain([&end]))
+            .all(|(entry, next)| {
+                entry.data_offset <= next.data_offset && entry.bit_offset <= next.bit_offset
+            });
+
+        if !sorted {
+            return Err(invalid_data("blocks index is corrupted"));
+        }
+
+        Ok(BlockIndex {
+            entries,
+            data_len,
+            bits_len,
+        })
+    }
+}
+
+/// Writes bits of all blocks one after another followed by blocks index.
+pub fn write_blocks(blocks: &[Block]) -> Vec<u8> {
+    let mut bits = Bits::new();
+
+    for block in blocks {
+        bits.append_bits(&block.bits);
+    }
+
+    let mut bytes = bits.get_bits().to_vec();
+    BlockIndex::from_blocks(blocks).write(&mut bytes);
+
+    bytes
+}
+
+/// Reads blocks written by `write_blocks`.
+pub fn read_blocks(bytes: &[u8]) -> io::Result<Vec<Block>> {
+    let index = BlockIndex::read(&mut Cursor::new(bytes))?;
+
+    // Index checked that bits fit in front of it.
+    let bits_bytes = &bytes[..index.bits_len.div_ceil(8)];
+    let bits = Bits::from_vec(index.bits_len, bits_bytes.to_vec());
+
+    let blocks = (0..index.entries.len())
+        .map(|block| {
+            let bit_range = index.bit_range(block);
+
+            Block {
+                data_len: index.data_range(block).len(),
+                bits: bits.slice(bit_range.start, bit_range.end),
+            }
+        })
+        .collect();
+
+    Ok(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EliasOmegaDecoder, EliasOmegaEncoder};
+
+    #[test]
+    fn blocks_roundtrip_works() {
+        let data: Vec<_> = (0..10_000).map(|n| (n % 7 * n % 13) as u8).collect();
+
+        let blocks = compress_blocks::<EliasOmegaEncoder>(&data, 1000, 4);
+        let decoded = decompress_blocks::<EliasOmegaDecoder>(&blocks, 3);
+
+        assert_eq!(10, blocks.len());
+        assert_eq!(data, decoded);
+    }
+
+    #[test]
+    #[should_panic(expected = "block size has to be positive")]
+    fn zero_block_size_panics() {
+        compress_blocks::<EliasOmegaEncoder>(b"abc", 0, 1);
+    }
+
+    #[test]
+    fn write_read_blocks_works() {
+        let data = b"abracadabra abracadabra".to_vec();
+
+        let blocks = compress_blocks::<EliasOmegaEncoder>(&data, 10, 2);
+        let read = read_blocks(&write_blocks(&blocks)).unwrap();
+
+        assert_eq!(
+            blocks.iter().map(|b| (b.data_len, b.bits.len())).collect::<Vec<_>>(),
+            read.iter().map(|b| (b.data_len, b.bits.len())).collect::<Vec<_>>()
+        );
+        assert_eq!(data, decompress_blocks::<EliasOmegaDecoder>(&read, 2));
+    }
+
+    #[test]
+    fn block_index_works() {
+        let data = b"abracadabra abracadabra".to_vec();
+
+        let blocks = compress_blocks::<EliasOmegaEncoder>(&data, 10, 2);
+        let bytes = write_blocks(&blocks);
+
+        let index = BlockIndex::read(&mut Cursor::new(&bytes)).unwrap();
+
+        assert_eq!(BlockIndex::from_blocks(&blocks), index);
+        assert_eq!(Some(0), index.find_block(9));
+        assert_eq!(Some(2), index.find_block(22));
+        assert_eq!(None, index.find_block(23));
+        assert_eq!(20..23, index.data_range(2));
+    }
+
+    #[test]
+    fn corrupted_index_is_rejected() {
+        let blocks = compress_blocks::<EliasOmegaEncoder>(b"abracadabra", 4, 1);
+        let bytes = write_blocks(&blocks);
+        let count_start = bytes.len() - USIZE_LEN;
+
+        for blocks_count in [usize::MAX, 1 << 40, 4] {
+            let mut corrupted = bytes.clone();
+            corrupted[count_start..].copy_from_slice(&blocks_count.to_be_bytes());
+
+            let error = read_blocks(&corrupted).err().unwrap();
+
+            assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        }
+
+        let error = read_blocks(&bytes[..USIZE_LEN]).err().unwrap();
+
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+    }
+}
@@ -0,0 +1,139 @@
+//! Things usefull for Burrows–Wheeler transform.
+
+/// Builds suffix array of `data` using prefix doubling.
+/// Shorter suffix is smaller than longer one sharing its prefix.
+pub fn suffix_array(data: &[u8]) -> Vec<usize> {
+    let len = data.len();
+
+    let mut suffixes: Vec<usize> = (0..len).collect();
+    let mut ranks: Vec<usize> = data.iter().map(|&s| s as usize).collect();
+    let mut new_ranks = vec![0; len];
+    let mut step = 1;
+
+    if len <= 1 {
+        return suffixes;
+    }
+
+    loop {
+        // Rank 0 is reserved for suffixes shorter than `step`.
+        let key = |i: usize| (ranks[i], ranks.get(i + step).map_or(0, |r| r + 1));
+
+        suffixes.sort_unstable_by_key(|&i| key(i));
+
+        new_ranks[suffixes[0]] = 0;
+
+        for i in 1..len {
+            let same = key(suffixes[i - 1]) == key(suffixes[i]);
+            new_ranks[suffixes[i]] = new_ranks[suffixes[i - 1]] + if same { 0 } else { 1 };
+        }
+
+        std::mem::swap(&mut ranks, &mut new_ranks);
+
+        if ranks[suffixes[len - 1]] == len - 1 {
+            break;
+        }
+
+        step *= 2;
+    }
+
+    suffixes
+}
+
+/// Transforms `data` into last column of sorted rotations of `data`
+/// with unique sentinel at the end. Sentinel is skipped in returned column
+/// and its position is returned as primary index.
+pub fn transform(data: &[u8]) -> (Vec<u8>, usize) {
+    let suffixes = suffix_array(data);
+
+    let mut last_column = Vec::with_capacity(data.len());
+    let mut primary_index = 0;
+
+    // First row is sentinel itself which is preceded by last symbol.
+    if let Some(&symbol) = data.last() {
+        last_column.push(symbol);
+    }
+
+    for (row, &suffix) in suffixes.iter().enumerate() {
+        match suffix {
+            0 => primary_index = row + 1,
+            _ => last_column.push(data[suffix - 1]),
+        }
+    }
+
+    (last_column, primary_index)
+}
+
+/// Reverts `transform` using last column and primary index.
+pub fn inverse(last_column: &[u8], primary_index: usize) -> Vec<u8> {
+    let len = last_column.len();
+
+    // Position of each row's symbol in last column, sentinel is skipped.
+    let symbol_at = |row: usize| last_column[if row < primary_index { row } else { row - 1 }];
+
+    let mut counts = [0; 256];
+
+    for &symbol in last_column {
+        counts[symbol as usize] += 1;
+    }
+
+    // First row of every symbol in first column, sentinel takes row 0.
+    let mut first_rows = [0; 256];
+    let mut row = 1;
+
+    for (first_row, count) in first_rows.iter_mut().zip(counts) {
+        *first_row = row;
+        row += count;
+    }
+
+    let mut next_rows = vec![0; len + 1];
+    let mut seen = [0; 256];
+
+    for (row, next_row) in next_rows.iter_mut().enumerate() {
+        if row == primary_index {
+            continue;
+        }
+
+        let symbol = symbol_at(row) as usize;
+        *next_row = first_rows[symbol] + seen[symbol];
+        seen[symbol] += 1;
+    }
+
+    let mut data = vec![0; len];
+    let mut row = 0;
+
+    for i in (0..len).rev() {
+        data[i] = symbol_at(row);
+        row = next_rows[row];
+    }
+
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suffix_array_works() {
+        let suffixes = suffix_array(b"banana");
+
+        assert_eq!(vec![5, 3, 1, 0, 4, 2], suffixes);
+    }
+
+    #[test]
+    fn transform_works() {
+        let (last_column, primary_index) = transform(b"banana");
+
+        assert_eq!(b"annbaa".to_vec(), last_column);
+        assert_eq!(4, primary_index);
+    }
+
+    #[test]
+    fn inverse_works() {
+        let data = b"abracadabra abracadabra".to_vec();
+
+        let (last_column, primary_index) = transform(&data);
+
+        assert_eq!(data, inverse(&last_column, primary_index));
+    }
+}
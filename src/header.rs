@@ -0,0 +1,175 @@
+//! Header written in front of every compressed file.
+//!
+//! It records algorithm and encoding tags, width of integers and transforms of numbers,
+//! so file can be decompressed without passing the same options again,
+//! and CRC-32 of uncompressed data, so round-trip can be verified.
+
+use std::io;
+
+use crate::io_utils::{self, crc32, invalid_data, USIZE_LEN};
+use crate::transforms::Transform;
+
+/// Bytes every compressed file starts with.
+pub const MAGIC: [u8; 4] = *b"UCMP";
+
+/// Version of header layout, bumped whenever it or meaning of tags changes.
+pub const VERSION: u8 = 5;
+
+/// Most transforms recorded in header.
+pub const MAX_TRANSFORMS: usize = 4;
+
+/// Widest integers, in bytes, data can be read as.
+pub const MAX_INTEGER_WIDTH: usize = std::mem::size_of::<u64>();
+
+/// Describes how data following the header was compressed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Header {
+    pub algorithm: u8,
+    pub encoding: u8,
+    /// Width in bytes of little endian integers data was read as, 1 for bytes.
+    pub integer_width: u8,
+    pub data_len: usize,
+    pub checksum: u32,
+    /// Id of trained LZW dictionary, 0 when none was used.
+    pub dictionary_id: u32,
+    /// Transforms applied to numbers before encoding them, in order.
+    pub transforms: [Option<Transform>; MAX_TRANSFORMS],
+}
+
+impl Header {
+    /// Length of written header in bytes.
+    pub const LEN: usize = MAGIC.len() + 4 + MAX_TRANSFORMS + USIZE_LEN + 2 * 4;
+
+    /// Creates new instance of `Header` describing uncompressed `data`.
+    pub fn new(algorithm: u8, encoding: u8, data: &[u8]) -> Header {
+        Header {
+            algorithm,
+            encoding,
+            integer_width: 1,
+            data_len: data.len(),
+            checksum: crc32(data),
+            dictionary_id: 0,
+            transforms: [None; MAX_TRANSFORMS],
+        }
+    }
+
+    /// Records `transforms` applied to numbers before encoding them.
+    pub fn with_transforms(mut self, transforms: &[Transform]) -> Header {
+        assert!(transforms.len() <= MAX_TRANSFORMS, "too many transforms for header");
+
+        self.transforms = [None; MAX_TRANSFORMS];
+
+        for (slot, &transform) in self.transforms.iter_mut().zip(transforms) {
+            *slot = Some(transform);
+        }
+
+        self
+    }
+
+    /// Returns recorded transforms in order of applying them.
+    pub fn transform_list(&self) -> Vec<Transform> {
+        self.transforms.iter().flatten().copied().collect()
+    }
+
+    pub fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&[
+            VERSION,
+            self.algorithm,
+            self.encoding,
+            self.integer_width,
+        ]);
+
+        // Tags are shifted by one, so 0 marks empty slot.
+        for transform in &self.transforms {
+            bytes.push(transform.map_or(0, |transform| transform.tag() as u8 + 1));
+        }
+
+        bytes.extend_from_slice(&self.data_len.to_be_bytes());
+        bytes.extend_from_slice(&self.checksum.to_be_bytes());
+        bytes.extend_from_slice(&self.dictionary_id.to_be_bytes());
+    }
+
+    /// Reads header from the front of `bytes`.
+    pub fn read(bytes: &[u8]) -> io::Result<Header> {
+        if bytes.len() < Header::LEN || bytes[..MAGIC.len()] != MAGIC {
+            return Err(invalid_data("file isn't compressed by universal_compressor"));
+        }
+
+        let bytes = &bytes[MAGIC.len()..Header::LEN];
+
+        if bytes[0] != VERSION {
+            return Err(invalid_data("unsupported header version"));
+        }
+
+        let integer_width = bytes[3];
+
+        if integer_width == 0 || integer_width as usize > MAX_INTEGER_WIDTH {
+            return Err(invalid_data("unsupported integer width"));
+        }
+
+        let (transform_tags, mut rest) = bytes[4..].split_at(MAX_TRANSFORMS);
+        let mut transforms = [None; MAX_TRANSFORMS];
+
+        for (slot, &tag) in transforms.iter_mut().zip(transform_tags) {
+            if tag != 0 {
+                let transform = Transform::from_tag(tag as usize - 1)
+                    .ok_or_else(|| invalid_data("unknown transform tag"))?;
+
+                *slot = Some(transform);
+            }
+        }
+
+        Ok(Header {
+            algorithm: bytes[1],
+            encoding: bytes[2],
+            integer_width,
+            data_len: io_utils::read_usize(&mut rest)?,
+            checksum: io_utils::read_u32(&mut rest)?,
+            dictionary_id: io_utils::read_u32(&mut rest)?,
+            transforms,
+        })
+    }
+
+    /// Checks that `data` has length and checksum recorded in header.
+    pub fn verify(&self, data: &[u8]) -> bool {
+        data.len() == self.data_len && crc32(data) == self.checksum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_roundtrip_works() {
+        let data = b"abracadabra";
+        let header = Header {
+            dictionary_id: 7,
+            integer_width: 4,
+            ..Header::new(2, 5, data)
+        }
+        .with_transforms(&[Transform::Delta, Transform::ZigZag]);
+
+        let mut bytes = vec![];
+        header.write(&mut bytes);
+        bytes.extend_from_slice(b"compressed data");
+
+        assert_eq!(Header::LEN + 15, bytes.len());
+        assert_eq!(header, Header::read(&bytes).unwrap());
+        assert!(header.verify(data));
+        assert!(!header.verify(b"abracadabrb"));
+        assert_eq!(vec![Transform::Delta, Transform::ZigZag], header.transform_list());
+
+        // Tag of the first transform.
+        bytes[MAGIC.len() + 4] = 100;
+        assert!(Header::read(&bytes).is_err());
+
+        bytes[MAGIC.len() + 4] = 0;
+        bytes[MAGIC.len() + 3] = 9;
+        assert!(Header::read(&bytes).is_err());
+
+        bytes[0] = b'X';
+        assert!(Header::read(&bytes).is_err());
+    }
+}
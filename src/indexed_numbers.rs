@@ -0,0 +1,210 @@
+//! Things usefull for reading single numbers of encoded stream without decoding it whole.
+
+use std::io;
+use std::marker::PhantomData;
+
+use crate::bits::Bits;
+use crate::io_utils::{self, invalid_data, read_usize};
+use crate::number_encoders::{NumberDecoder, NumberEncoder};
+
+/// Numbers encoded in chunks of `sample_rate` with bit offset of every chunk
+/// (sample) kept aside. Number is read by decoding its chunk from the start
+/// up to the number, every chunk is encoded separately, so any encoding
+/// works, even one writing tables like huffman.
+pub struct IndexedNumbers<D> {
+    bits: Bits,
+    len: usize,
+    sample_rate: usize,
+    samples: Vec<usize>,
+    decoder: PhantomData<D>,
+}
+
+impl<D> IndexedNumbers<D>
+where
+    D: NumberDecoder,
+{
+    /// Encodes `numbers` with `E`, which has to be decodable by `D`.
+    pub fn encode<E>(numbers: &[usize], sample_rate: usize) -> IndexedNumbers<D>
+    where
+        E: NumberEncoder,
+    {
+        assert!(sample_rate > 0, "sample rate has to be positive");
+
+        let mut bits = Bits::new();
+        let mut samples = vec![];
+
+        for chunk in numbers.chunks(sample_rate) {
+            samples.push(bits.len());
+
+            // Needed because elias can't handle 0.
+            let chunk: Vec<_> = chunk.iter().map(|number| number + 1).collect();
+
+            bits.append_bits(&E::encode(&chunk));
+        }
+
+        IndexedNumbers {
+            bits,
+            len: numbers.len(),
+            sample_rate,
+            samples,
+            decoder: PhantomData,
+        }
+    }
+
+    /// Returns encoded numbers without samples.
+    pub fn bits(&self) -> &Bits {
+        &self.bits
+    }
+
+    /// Returns number of encoded numbers.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if there are no encoded numbers.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `index`-th number. Only numbers of its sample up to it are decoded.
+    pub fn get(&self, index: usize) -> Option<usize> {
+        if index >= self.len {
+            return None;
+        }
+
+        let sample = index / self.sample_rate;
+
+        self.decode_sample(sample, index % self.sample_rate + 1).pop()
+    }
+
+    /// Decodes all numbers.
+    pub fn to_vec(&self) -> Vec<usize> {
+        (0..self.samples.len())
+            .flat_map(|sample| self.decode_sample(sample, self.sample_rate))
+            .collect()
+    }
+
+    /// Writes numbers count, sample rate, samples and encoded bits.
+    pub fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.len.to_be_bytes());
+        bytes.extend_from_slice(&self.sample_rate.to_be_bytes());
+
+        for sample in &self.samples {
+            bytes.extend_from_slice(&sample.to_be_bytes());
+        }
+
+        bytes.extend_from_slice(&self.bits.len().to_be_bytes());
+        bytes.extend_from_slice(self.bits.get_bits());
+    }
+
+    /// Reads numbers written by `write`, which have to be decodable by `D`.
+    pub fn read(bytes: &[u8]) -> io::Result<IndexedNumbers<D>> {
+        let mut bytes = bytes;
+
+        let len = read_usize(&mut bytes)?;
+        let sample_rate = read_usize(&mut bytes)?;
+
+        if sample_rate == 0 {
+            return Err(invalid_data("sample rate has to be positive"));
+        }
+
+        let samples = (0..len.div_ceil(sample_rate))
+            .map(|_| read_usize(&mut bytes))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let bits_len = read_usize(&mut bytes)?;
+        let bits_bytes = io_utils::take(&mut bytes, bits_len.div_ceil(8))?;
+        let bits = Bits::from_vec(bits_len, bits_bytes.to_vec());
+
+        let ordered = samples.windows(2).all(|pair| pair[0] <= pair[1]);
+
+        if !ordered || samples.last().is_some_and(|&sample| sample > bits_len) {
+            return Err(invalid_data("samples of indexed numbers are corrupted"));
+        }
+
+        Ok(IndexedNumbers {
+            bits,
+            len,
+            sample_rate,
+            samples,
+            decoder: PhantomData,
+        })
+    }
+
+    /// Decodes the first `count` numbers of `sample`.
+    fn decode_sample(&self, sample: usize, count: usize) -> Vec<usize> {
+        let start = self.samples[sample];
+        let end = self.samples.get(sample + 1).copied().unwrap_or(self.bits.len());
+
+        // Needed because elias can't handle 0.
+        D::decode_prefix(&self.bits.slice(start, end), count)
+            .into_iter()
+            .map(|number| number - 1)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        EliasDeltaDecoder, EliasDeltaEncoder, FibbonaciDecoder, FibbonaciEncoder, HuffmanDecoder,
+        HuffmanEncoder,
+    };
+
+    #[test]
+    fn get_works() {
+        let numbers: Vec<_> = (0..1000).map(|i| i * 37 % 101).collect();
+
+        let indexed = IndexedNumbers::<EliasDeltaDecoder>::encode::<EliasDeltaEncoder>(&numbers, 64);
+
+        assert_eq!(1000, indexed.len());
+
+        for (index, &number) in numbers.iter().enumerate() {
+            assert_eq!(Some(number), indexed.get(index));
+        }
+
+        assert_eq!(None, indexed.get(1000));
+        assert_eq!(numbers, indexed.to_vec());
+    }
+
+    #[test]
+    fn fibbonaci_works() {
+        let numbers = [0, 5, 0, 1000, 7];
+
+        let indexed = IndexedNumbers::<FibbonaciDecoder>::encode::<FibbonaciEncoder>(&numbers, 2);
+
+        assert_eq!(Some(1000), indexed.get(3));
+        assert_eq!(Some(7), indexed.get(4));
+        assert!(IndexedNumbers::<FibbonaciDecoder>::encode::<FibbonaciEncoder>(&[], 2).is_empty());
+    }
+
+    #[test]
+    fn huffman_works() {
+        let numbers: Vec<_> = (0..500).map(|i| i % 7 * i % 5).collect();
+
+        let indexed = IndexedNumbers::<HuffmanDecoder>::encode::<HuffmanEncoder>(&numbers, 100);
+
+        assert_eq!(Some(numbers[250]), indexed.get(250));
+        assert_eq!(numbers, indexed.to_vec());
+    }
+
+    #[test]
+    fn write_read_works() {
+        let numbers: Vec<_> = (0..1000).map(|i| i * 37 % 101).collect();
+
+        let indexed = IndexedNumbers::<EliasDeltaDecoder>::encode::<EliasDeltaEncoder>(&numbers, 64);
+
+        let mut bytes = vec![];
+        indexed.write(&mut bytes);
+
+        let read = IndexedNumbers::<EliasDeltaDecoder>::read(&bytes).unwrap();
+
+        assert_eq!(indexed.samples, read.samples);
+        assert_eq!(Some(numbers[777]), read.get(777));
+        assert_eq!(numbers, read.to_vec());
+
+        let truncated = IndexedNumbers::<EliasDeltaDecoder>::read(&bytes[..bytes.len() - 1]);
+        assert_eq!(io::ErrorKind::InvalidData, truncated.err().unwrap().kind());
+    }
+}
@@ -0,0 +1,84 @@
+//! Things shared by readers and writers of binary formats.
+
+use std::io;
+
+/// Length of big endian `usize` written in binary formats.
+pub const USIZE_LEN: usize = std::mem::size_of::<usize>();
+
+/// Takes `len` bytes from the front of `bytes` and advances them.
+pub fn take<'a>(bytes: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if bytes.len() < len {
+        return Err(invalid_data("data is truncated"));
+    }
+
+    let (taken, tail) = bytes.split_at(len);
+    *bytes = tail;
+
+    Ok(taken)
+}
+
+/// Reads big endian `usize` from the front of `bytes` and advances them.
+pub fn read_usize(bytes: &mut &[u8]) -> io::Result<usize> {
+    Ok(usize::from_be_bytes(take(bytes, USIZE_LEN)?.try_into().unwrap()))
+}
+
+/// Reads big endian `u32` from the front of `bytes` and advances them.
+pub fn read_u32(bytes: &mut &[u8]) -> io::Result<u32> {
+    Ok(u32::from_be_bytes(take(bytes, 4)?.try_into().unwrap()))
+}
+
+pub fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = match crc & 1 {
+                1 => 0xEDB8_8320 ^ (crc >> 1),
+                _ => crc >> 1,
+            };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
+
+/// Computes CRC-32 (the one used by gzip and zip) of `data`.
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, &byte| {
+        CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_works() {
+        assert_eq!(0, crc32(b""));
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+    }
+
+    #[test]
+    fn take_works() {
+        let mut bytes = &[1, 2, 3][..];
+
+        assert_eq!([1, 2], take(&mut bytes, 2).unwrap());
+        assert_eq!(io::ErrorKind::InvalidData, take(&mut bytes, 2).unwrap_err().kind());
+        assert_eq!([3], bytes);
+    }
+}
@@ -1,36 +1,384 @@
+pub mod analysis;
+pub mod archive;
 pub mod bits;
+pub mod blocks;
+pub mod bwt;
+pub mod header;
+pub mod indexed_numbers;
+pub mod io_utils;
+pub mod lz78;
 pub mod lzw;
+pub mod messages;
+pub mod mtf;
 pub mod number_encoders;
+pub mod report;
+pub mod seekable;
+pub mod stats;
+pub mod stream;
+pub mod transforms;
 
-pub use number_encoders::{NumberDecoder, NumberEncoder};
+pub use number_encoders::{
+    AdaptiveNumberDecoder, AdaptiveNumberEncoder, CodeLength, NumberDecoder, NumberEncoder,
+};
 
+use lz78::lz78_decoder::Lz78Decoder;
+use lz78::lz78_encoder::Lz78Encoder;
+use lz78::Lz78Code;
 use lzw::lzw_decoder::LzwDecoder;
 use lzw::lzw_encoder::LzwEncoder;
+use lzw::trained_dictionary::TrainedDictionary;
+use mtf::MoveToFront;
+use report::CompressionStats;
+use std::hash::Hash;
+use std::time::Instant;
 
 pub use bits::Bits;
+pub use number_encoders::arithmetic::{ArithmeticDecoder, ArithmeticEncoder};
+pub use number_encoders::auto::{AutoDecoder, AutoEncoder};
 pub use number_encoders::elias::omega::{EliasOmegaDecoder, EliasOmegaEncoder};
 pub use number_encoders::elias::delta::{EliasDeltaDecoder, EliasDeltaEncoder};
 pub use number_encoders::elias::gamma::{EliasGammaDecoder, EliasGammaEncoder};
+pub use number_encoders::elias_fano::{EliasFano, EliasFanoDecoder, EliasFanoEncoder};
 pub use number_encoders::fibbonaci::{FibbonaciDecoder, FibbonaciEncoder};
+pub use number_encoders::huffman::{HuffmanDecoder, HuffmanEncoder};
+pub use number_encoders::interpolative::{InterpolativeDecoder, InterpolativeEncoder};
+pub use number_encoders::range::{RangeDecoder, RangeEncoder};
+pub use number_encoders::pfor::{PForDeltaDecoder, PForDeltaEncoder};
+pub use number_encoders::rans::{RansDecoder, RansEncoder};
+pub use number_encoders::simple::{Simple16Decoder, Simple16Encoder, Simple9Decoder, Simple9Encoder};
+pub use number_encoders::raw::{RawByteDecoder, RawByteEncoder};
+pub use number_encoders::truncated_binary::{TruncatedBinaryDecoder, TruncatedBinaryEncoder};
 
 pub fn encode<E>(data: &[u8]) -> Bits where E: NumberEncoder {
-    let encoded_lzw = LzwEncoder::new().encode_text(&data);
+    encode_with(data, E::encode)
+}
+
+pub fn decode<D>(data: &Bits) -> Vec<u8> where D: NumberDecoder {
+    decode_with(data, D::decode)
+}
+
+/// Same as `encode`, but numbers encoding function can be chosen
+/// at runtime, e.g. from registered `number_encoders::CODECS`.
+pub fn encode_with(data: &[u8], encode: fn(&[usize]) -> Bits) -> Bits {
+    encode_with_stats(data, encode).0
+}
+
+/// Same as `encode_with`, but also returns statistics of both stages.
+pub fn encode_with_stats(data: &[u8], encode: fn(&[usize]) -> Bits) -> (Bits, CompressionStats) {
+    encode_lzw_with_stats(LzwEncoder::new(), data, encode)
+}
+
+/// Same as `encode`, but LZW starts from trained `dictionary`.
+pub fn encode_trained<E>(data: &[u8], dictionary: &TrainedDictionary) -> Bits
+where
+    E: NumberEncoder,
+{
+    encode_trained_with_stats(data, E::encode, dictionary).0
+}
+
+/// Same as `encode_with_stats`, but LZW starts from trained `dictionary`.
+pub fn encode_trained_with_stats(
+    data: &[u8],
+    encode: fn(&[usize]) -> Bits,
+    dictionary: &TrainedDictionary,
+) -> (Bits, CompressionStats) {
+    encode_lzw_with_stats(LzwEncoder::with_dictionary(dictionary), data, encode)
+}
+
+fn encode_lzw_with_stats(
+    mut lzw_encoder: LzwEncoder,
+    data: &[u8],
+    encode: fn(&[usize]) -> Bits,
+) -> (Bits, CompressionStats) {
+    let start = Instant::now();
+
+    let encoded_lzw = lzw_encoder.encode_text(data);
+
+    let dictionary_time = start.elapsed();
+    let start = Instant::now();
 
     // Needed because elias can't handle 0.
-    let encoded_lzw: Vec<_> = encoded_lzw.into_iter().map(|s| s + 1).collect();
+    let numbers: Vec<_> = encoded_lzw.iter().map(|s| s + 1).collect();
 
-    let encoded_numbers = E::encode(&encoded_lzw);
+    let encoded_numbers = encode(&numbers);
 
-    encoded_numbers
+    let stats = CompressionStats::new(
+        data.len(),
+        encoded_numbers.len(),
+        &encoded_lzw,
+        lzw_encoder.dictionary_size(),
+        dictionary_time,
+        start.elapsed(),
+    );
+
+    (encoded_numbers, stats)
 }
 
-pub fn decode<D>(data: &Bits) -> Vec<u8> where D: NumberDecoder {
-    let decoded_numbers = D::decode(&data);
+pub fn decode_with(data: &Bits, decode: fn(&Bits) -> Vec<usize>) -> Vec<u8> {
+    decode_lzw_with(LzwDecoder::new(), data, decode)
+}
+
+pub fn decode_trained<D>(data: &Bits, dictionary: &TrainedDictionary) -> Vec<u8>
+where
+    D: NumberDecoder,
+{
+    decode_trained_with(data, D::decode, dictionary)
+}
+
+pub fn decode_trained_with(
+    data: &Bits,
+    decode: fn(&Bits) -> Vec<usize>,
+    dictionary: &TrainedDictionary,
+) -> Vec<u8> {
+    decode_lzw_with(LzwDecoder::with_dictionary(dictionary), data, decode)
+}
+
+fn decode_lzw_with(
+    mut lzw_decoder: LzwDecoder,
+    data: &Bits,
+    decode: fn(&Bits) -> Vec<usize>,
+) -> Vec<u8> {
+    let decoded_numbers = decode(data);
 
     // Needed because elias can't handle 0.
     let decoded_numbers: Vec<_> = decoded_numbers.into_iter().map(|s| s - 1).collect();
 
-    let decoded = LzwDecoder::new().decode_text(&decoded_numbers);
+    lzw_decoder.decode_text(&decoded_numbers)
+}
+
+/// Same as `encode`, but LZW runs on `symbols` of any type, e.g. tokens,
+/// with initial dictionary of `alphabet`. The same `alphabet` has to be
+/// passed to `decode_symbols`.
+pub fn encode_symbols<T, E>(symbols: &[T], alphabet: &[T]) -> Bits
+where
+    T: Eq + Hash + Clone,
+    E: NumberEncoder,
+{
+    let encoded_lzw = LzwEncoder::with_alphabet(alphabet).encode_text(symbols);
+
+    // Needed because elias can't handle 0.
+    let numbers: Vec<_> = encoded_lzw.iter().map(|s| s + 1).collect();
+
+    E::encode(&numbers)
+}
+
+pub fn decode_symbols<T, D>(data: &Bits, alphabet: &[T]) -> Vec<T>
+where
+    T: Clone,
+    D: NumberDecoder,
+{
+    // Needed because elias can't handle 0.
+    let decoded_numbers: Vec<_> = D::decode(data).into_iter().map(|s| s - 1).collect();
+
+    LzwDecoder::with_alphabet(alphabet).decode_text(&decoded_numbers)
+}
+
+/// Predicts length in bits of `data` encoded by `encode::<E>`
+/// without creating encoded `Bits`.
+pub fn encoded_size<E>(data: &[u8]) -> usize
+where
+    E: CodeLength,
+{
+    let encoded_lzw = LzwEncoder::new().encode_text(data);
+
+    // Needed because elias can't handle 0.
+    encoded_lzw.into_iter().map(|s| E::code_len(s + 1)).sum()
+}
+
+const CODES_COUNT_WIDTH: usize = 64;
+
+/// Encodes `data` with LZW and adaptive encoder `E`. Every code is encoded
+/// with current dictionary size as its bound. Codes count is written first,
+/// because adaptive encoders don't know where their stream ends.
+pub fn encode_adaptive<E>(data: &[u8]) -> Bits
+where
+    E: AdaptiveNumberEncoder,
+{
+    encode_adaptive_with_stats::<E>(data).0
+}
+
+/// Same as `encode_adaptive`, but also returns statistics of both stages.
+pub fn encode_adaptive_with_stats<E>(data: &[u8]) -> (Bits, CompressionStats)
+where
+    E: AdaptiveNumberEncoder,
+{
+    let start = Instant::now();
+
+    let mut lzw_encoder = LzwEncoder::new();
+    let encoded_lzw = lzw_encoder.encode_text(data);
+
+    let dictionary_time = start.elapsed();
+    let start = Instant::now();
+
+    let mut encoder = E::new();
+
+    for (i, &code) in encoded_lzw.iter().enumerate() {
+        encoder.encode_number(code, initial_dictionary_size() + i);
+    }
+
+    let mut bits = Bits::new();
+    bits.push_number(encoded_lzw.len(), CODES_COUNT_WIDTH);
+    bits.append_bits(&encoder.finish());
+
+    let stats = CompressionStats::new(
+        data.len(),
+        bits.len(),
+        &encoded_lzw,
+        lzw_encoder.dictionary_size(),
+        dictionary_time,
+        start.elapsed(),
+    );
+
+    (bits, stats)
+}
+
+pub fn decode_adaptive<D>(data: &Bits) -> Vec<u8>
+where
+    D: AdaptiveNumberDecoder,
+{
+    let codes_count: usize = data.slice(0, CODES_COUNT_WIDTH).into();
+
+    let mut decoder = D::new(data.slice(CODES_COUNT_WIDTH, data.len()));
+
+    let decoded_numbers: Vec<_> = (0..codes_count)
+        .map(|i| decoder.decode_number(initial_dictionary_size() + i))
+        .collect();
+
+    LzwDecoder::new().decode_text(&decoded_numbers)
+}
+
+fn initial_dictionary_size() -> usize {
+    lzw::ALPHABET_SIZE as usize + 1
+}
+
+/// Encodes raw bytes of `data` with `E` without any dictionary stage.
+pub fn encode_raw<E>(data: &[u8]) -> Bits
+where
+    E: NumberEncoder,
+{
+    encode_raw_with(data, E::encode)
+}
+
+pub fn decode_raw<D>(data: &Bits) -> Vec<u8>
+where
+    D: NumberDecoder,
+{
+    decode_raw_with(data, D::decode)
+}
+
+/// Works like `encode_raw`, but encoder can be chosen at runtime.
+pub fn encode_raw_with(data: &[u8], encode: fn(&[usize]) -> Bits) -> Bits {
+    // Needed because elias can't handle 0.
+    let numbers: Vec<_> = data.iter().map(|&s| s as usize + 1).collect();
+
+    encode(&numbers)
+}
+
+pub fn decode_raw_with(data: &Bits, decode: fn(&Bits) -> Vec<usize>) -> Vec<u8> {
+    // Needed because elias can't handle 0.
+    decode(data).into_iter().map(|n| (n - 1) as u8).collect()
+}
+
+/// Encodes `data` with LZ78. Phrase indices are encoded with `E` and
+/// next symbols with `S` (e.g. `RawByteEncoder`), both streams are returned
+/// separately as `(indices, symbols)`.
+pub fn encode_lz78<E, S>(data: &[u8]) -> (Bits, Bits)
+where
+    E: NumberEncoder,
+    S: NumberEncoder,
+{
+    encode_lz78_with(data, E::encode, S::encode)
+}
+
+pub fn decode_lz78<D, S>(indices: &Bits, symbols: &Bits) -> Vec<u8>
+where
+    D: NumberDecoder,
+    S: NumberDecoder,
+{
+    decode_lz78_with(indices, symbols, D::decode, S::decode)
+}
+
+/// Works like `encode_lz78`, but encoders can be chosen at runtime.
+pub fn encode_lz78_with(
+    data: &[u8],
+    encode_indices: fn(&[usize]) -> Bits,
+    encode_symbols: fn(&[usize]) -> Bits,
+) -> (Bits, Bits) {
+    let encoded_lz78 = Lz78Encoder::new().encode_text(data);
+
+    // Needed because elias can't handle 0.
+    let indices: Vec<_> = encoded_lz78.iter().map(|c| c.index + 1).collect();
+    let symbols: Vec<_> = encoded_lz78
+        .iter()
+        .filter_map(|c| c.symbol)
+        .map(|s| s as usize + 1)
+        .collect();
+
+    (encode_indices(&indices), encode_symbols(&symbols))
+}
+
+pub fn decode_lz78_with(
+    indices: &Bits,
+    symbols: &Bits,
+    decode_indices: fn(&Bits) -> Vec<usize>,
+    decode_symbols: fn(&Bits) -> Vec<usize>,
+) -> Vec<u8> {
+    let indices = decode_indices(indices);
+    let mut symbols = decode_symbols(symbols).into_iter();
+
+    // Needed because elias can't handle 0.
+    let codes: Vec<_> = indices
+        .into_iter()
+        .map(|index| Lz78Code {
+            index: index - 1,
+            symbol: symbols.next().map(|s| (s - 1) as u8),
+        })
+        .collect();
+
+    Lz78Decoder::new().decode_text(&codes)
+}
+
+/// Encodes `data` with Burrows–Wheeler transform followed by move-to-front.
+/// Primary index is encoded with `E` as the first number of the stream.
+pub fn encode_bwt<E>(data: &[u8]) -> Bits
+where
+    E: NumberEncoder,
+{
+    encode_bwt_with(data, E::encode)
+}
+
+pub fn decode_bwt<D>(data: &Bits) -> Vec<u8>
+where
+    D: NumberDecoder,
+{
+    decode_bwt_with(data, D::decode)
+}
+
+/// Works like `encode_bwt`, but encoder can be chosen at runtime.
+pub fn encode_bwt_with(data: &[u8], encode: fn(&[usize]) -> Bits) -> Bits {
+    let (last_column, primary_index) = bwt::transform(data);
+    let indices = MoveToFront::new().encode_text(&last_column);
+
+    // Needed because elias can't handle 0.
+    let numbers: Vec<_> = std::iter::once(primary_index)
+        .chain(indices)
+        .map(|n| n + 1)
+        .collect();
+
+    encode(&numbers)
+}
+
+pub fn decode_bwt_with(data: &Bits, decode: fn(&Bits) -> Vec<usize>) -> Vec<u8> {
+    let decoded_numbers = decode(data);
+
+    // Needed because elias can't handle 0.
+    let decoded_numbers: Vec<_> = decoded_numbers.into_iter().map(|n| n - 1).collect();
+
+    let (&primary_index, indices) = decoded_numbers
+        .split_first()
+        .expect("primary index is missing");
+
+    let last_column = MoveToFront::new().decode_text(indices);
 
-    decoded
+    bwt::inverse(&last_column, primary_index)
 }
@@ -0,0 +1,28 @@
+pub mod lz78_decoder;
+pub mod lz78_encoder;
+
+use std::collections::HashMap;
+
+use crate::lzw::word::Word;
+
+pub type Dictionary = Vec<Word>;
+pub type HashMapDictionary = HashMap<Word, usize>;
+
+/// Single LZ78 output pair: index of the longest known phrase and the
+/// symbol following it. Symbol is missing only for the last pair, when
+/// the text ends inside a phrase already present in the dictionary.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Lz78Code {
+    pub index: usize,
+    pub symbol: Option<u8>,
+}
+
+/// Creates initial dictionary for Lz78Decoder.
+pub fn create_dictionary() -> Dictionary {
+    vec![Word::new()]
+}
+
+/// Creates initial dictionary for Lz78Encoder.
+pub fn create_hashmap_dictionary() -> HashMapDictionary {
+    [(Word::new(), 0)].into_iter().collect()
+}
@@ -0,0 +1,72 @@
+//! Things usefull for decoding LZ78 encoded data.
+
+use crate::lz78::{self, Dictionary, Lz78Code};
+
+/// Used to decode LZ78 encoded data.
+pub struct Lz78Decoder {
+    dictionary: Dictionary,
+}
+
+impl Lz78Decoder {
+    /// Creates new instance of `Lz78Decoder` with dictionary containing
+    /// only empty phrase.
+    pub fn new() -> Lz78Decoder {
+        Lz78Decoder {
+            dictionary: lz78::create_dictionary(),
+        }
+    }
+
+    /// Decodes LZ78 encoded `codes` into `Vec<u8>`.
+    pub fn decode_text(&mut self, codes: &[Lz78Code]) -> Vec<u8> {
+        let mut text = Vec::new();
+
+        for code in codes {
+            let mut word = self
+                .dictionary
+                .get(code.index)
+                .expect("phrase index not in dictionary")
+                .clone();
+
+            if let Some(symbol) = code.symbol {
+                word.add_symbol(symbol);
+                self.dictionary.push(word.clone());
+            }
+
+            text.extend_from_slice(word.get_symbols_ref());
+        }
+
+        text
+    }
+}
+
+impl Default for Lz78Decoder {
+    fn default() -> Self {
+        Lz78Decoder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lz78_decode_works() {
+        let mut lz78_decode_dict = Lz78Decoder::new();
+        let codes = [
+            (0, Some(0)),
+            (0, Some(1)),
+            (1, Some(1)),
+            (3, Some(0)),
+            (2, Some(0)),
+            (2, None),
+        ];
+        let codes: Vec<_> = codes
+            .into_iter()
+            .map(|(index, symbol)| Lz78Code { index, symbol })
+            .collect();
+
+        let text = lz78_decode_dict.decode_text(&codes);
+
+        assert_eq!(vec![0, 1, 0, 1, 0, 1, 0, 1, 0, 1], text);
+    }
+}
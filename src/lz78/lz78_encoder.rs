@@ -0,0 +1,117 @@
+//! Things usefull for encoding data with LZ78.
+
+use crate::lz78::{self, HashMapDictionary, Lz78Code};
+use crate::lzw::word::Word;
+
+/// Used to encode data with LZ78.
+pub struct Lz78Encoder {
+    dictionary: HashMapDictionary,
+    word_code: usize,
+}
+
+impl Lz78Encoder {
+    /// Creates new instance of `Lz78Encoder` with dictionary containing
+    /// only empty phrase.
+    pub fn new() -> Self {
+        Lz78Encoder {
+            dictionary: lz78::create_hashmap_dictionary(),
+            word_code: 1,
+        }
+    }
+
+    /// Encodes `text` using LZ78 encoding into `Vec<Lz78Code>`.
+    pub fn encode_text(&mut self, text: &[u8]) -> Vec<Lz78Code> {
+        let mut symbols = text.iter().copied();
+
+        let mut codes = vec![];
+
+        while let Some(code) = self.get_next_code(&mut symbols) {
+            codes.push(code);
+        }
+
+        codes
+    }
+
+    /// Gets next pair from `symbols` iterator and updates dictionary.
+    fn get_next_code<I>(&mut self, symbols: &mut I) -> Option<Lz78Code>
+    where
+        I: Iterator<Item = u8>,
+    {
+        let mut curr_word = Word::new();
+
+        loop {
+            let symbol = match symbols.next() {
+                Some(symbol) => symbol,
+                None if curr_word.is_empty() => return None,
+                None => return Some(self.end_encoding(&curr_word)),
+            };
+
+            let index = self.get_word_code(&curr_word);
+            curr_word.add_symbol(symbol);
+
+            if !self.dictionary.contains_key(&curr_word) {
+                self.dictionary.insert(curr_word, self.word_code);
+                self.word_code += 1;
+
+                return Some(Lz78Code {
+                    index,
+                    symbol: Some(symbol),
+                });
+            }
+        }
+    }
+
+    // Returns pair for the phrase which was cut by the end of text.
+    fn end_encoding(&self, curr_word: &Word) -> Lz78Code {
+        Lz78Code {
+            index: self.get_word_code(curr_word),
+            symbol: None,
+        }
+    }
+
+    // Make sure that word exists in dictionary !!!
+    fn get_word_code(&self, word: &Word) -> usize {
+        *self.dictionary.get(word).expect("word not in dictionary")
+    }
+}
+
+impl Default for Lz78Encoder {
+    fn default() -> Self {
+        Lz78Encoder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initialization_works() {
+        let lz78_dict = Lz78Encoder::new();
+
+        assert_eq!(1, lz78_dict.dictionary.len());
+    }
+
+    #[test]
+    fn lz78_works() {
+        let mut lz78_dict = Lz78Encoder::new();
+        let symbols = [0, 1, 0, 1, 0, 1, 0, 1, 0, 1];
+
+        let codes = lz78_dict.encode_text(&symbols);
+
+        let expected = [
+            (0, Some(0)),
+            (0, Some(1)),
+            (1, Some(1)),
+            (3, Some(0)),
+            (2, Some(0)),
+            (2, None),
+        ];
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|(index, symbol)| Lz78Code { index, symbol })
+            .collect();
+
+        assert_eq!(expected, codes);
+    }
+}
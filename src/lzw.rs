@@ -1,22 +1,41 @@
 pub mod lzw_decoder;
 pub mod lzw_encoder;
+pub mod trained_dictionary;
 pub mod word;
 
 use std::collections::HashMap;
+use std::hash::Hash;
 
 use word::Word;
 
 pub const ALPHABET_SIZE: u8 = 255;
 
-pub type Dictionary = Vec<Word>;
-pub type HashMapDictionary = HashMap<Word, usize>;
+pub type Dictionary<T = u8> = Vec<Word<T>>;
+pub type HashMapDictionary<T = u8> = HashMap<Word<T>, usize>;
 
-/// Creates initial dictionary for LzwDecoder.
-pub fn create_dictionary() -> Dictionary {
-    (0..=ALPHABET_SIZE).map(|n| Word::from_vec(vec![n])).collect()
+/// Returns all bytes, which are initial alphabet of byte LZW.
+pub fn byte_alphabet() -> Vec<u8> {
+    (0..=ALPHABET_SIZE).collect()
 }
 
-/// Creates initial dictionary for LzwEncoder.
-pub fn create_hashmap_dictionary() -> HashMapDictionary {
-    (0..=ALPHABET_SIZE).map(|n| (Word::from_vec(vec![n]), n as usize)).collect()
+/// Creates initial dictionary for LzwDecoder with word for every
+/// symbol of `alphabet`. Symbol's code is its position in `alphabet`.
+pub fn create_dictionary<T>(alphabet: &[T]) -> Dictionary<T>
+where
+    T: Clone,
+{
+    alphabet.iter().map(|n| Word::from_vec(vec![n.clone()])).collect()
+}
+
+/// Creates initial dictionary for LzwEncoder with word for every
+/// symbol of `alphabet`. Symbol's code is its position in `alphabet`.
+pub fn create_hashmap_dictionary<T>(alphabet: &[T]) -> HashMapDictionary<T>
+where
+    T: Eq + Hash + Clone,
+{
+    alphabet
+        .iter()
+        .enumerate()
+        .map(|(code, n)| (Word::from_vec(vec![n.clone()]), code))
+        .collect()
 }
@@ -1,26 +1,55 @@
 //! Things usefull for decoding LZW encoded data.
 
+use crate::lzw::trained_dictionary::TrainedDictionary;
 use crate::lzw::{self, Dictionary, Word};
 
-/// Used to decode LZW encoded data.
-pub struct LzwDecoder {
-    dictionary: Dictionary,
-    last_word: Option<Word>,
+/// Used to decode LZW encoded data. Symbols are bytes by default.
+pub struct LzwDecoder<T = u8> {
+    dictionary: Dictionary<T>,
+    last_word: Option<Word<T>>,
 }
 
 impl LzwDecoder {
     /// Creates new instance of `LzwDecoder` with dictionary initialized
     /// to all ASCII symbols.
     pub fn new() -> LzwDecoder {
+        LzwDecoder::with_alphabet(&lzw::byte_alphabet())
+    }
+
+    /// Creates new instance of `LzwDecoder` with dictionary initialized
+    /// to all ASCII symbols followed by words of trained `dictionary`.
+    pub fn with_dictionary(dictionary: &TrainedDictionary) -> LzwDecoder {
+        let mut lzw_decoder = LzwDecoder::new();
+        lzw_decoder.dictionary.extend_from_slice(dictionary.words());
+
+        lzw_decoder
+    }
+}
+
+impl<T> LzwDecoder<T>
+where
+    T: Clone,
+{
+    /// Creates new instance of `LzwDecoder` with dictionary initialized
+    /// to symbols of `alphabet`, which get codes by their position.
+    pub fn with_alphabet(alphabet: &[T]) -> LzwDecoder<T> {
         LzwDecoder {
-            dictionary: lzw::create_dictionary(),
+            dictionary: lzw::create_dictionary(alphabet),
             last_word: None,
         }
     }
 
-    /// Decodes LZW encoded `codes` into `Vec<u8>`.
-    pub fn decode_text(&mut self, text: &[usize]) -> Vec<u8> {
-        let mut codes = text.into_iter().map(|n| *n);
+    /// Decodes LZW encoded `codes` into `Vec<T>`.
+    pub fn decode_text(&mut self, text: &[usize]) -> Vec<T> {
+        self.decode_words(text)
+            .into_iter()
+            .flat_map(Word::get_symbols)
+            .collect()
+    }
+
+    /// Decodes LZW encoded `codes` into words they stand for.
+    pub fn decode_words(&mut self, text: &[usize]) -> Vec<Word<T>> {
+        let mut codes = text.iter().copied();
 
         let mut words = Vec::new();
 
@@ -29,15 +58,11 @@ impl LzwDecoder {
         }
 
         words
-            .into_iter()
-            .map(Word::get_symbols)
-            .flatten()
-            .collect()
     }
 
     /// Fetches next code from `codes` iterator, transforms it into
     /// word and updates dictionary to handle the rest of codes.
-    fn get_next_word<I>(&mut self, codes: &mut I) -> Option<Word>
+    fn get_next_word<I>(&mut self, codes: &mut I) -> Option<Word<T>>
     where
         I: Iterator<Item = usize>,
     {
@@ -51,7 +76,7 @@ impl LzwDecoder {
 
     /// If last word exists it gets updated with first symbol
     /// of current word. Sets word as new last word.
-    fn word_in_dictionary(&mut self, word: Word) -> Option<Word> {
+    fn word_in_dictionary(&mut self, word: Word<T>) -> Option<Word<T>> {
         if let Some(mut last_word) = self.last_word.take() {
             last_word.add_symbol(word.get_first_symbol());
             self.dictionary.push(last_word);
@@ -64,7 +89,7 @@ impl LzwDecoder {
 
     /// Updates last word with first symbol of itself, adds last word
     /// to dictionary and sets updated last word as new last word.
-    fn word_not_in_dictionary(&mut self) -> Option<Word> {
+    fn word_not_in_dictionary(&mut self) -> Option<Word<T>> {
         let mut last_word = self.last_word.take().expect("there has to exist last word");
         last_word.add_symbol(last_word.get_first_symbol());
 
@@ -75,14 +100,21 @@ impl LzwDecoder {
     }
 
     /// Finds word in dictionary.
-    fn find_word(&self, code: usize) -> Option<Word> {
-        self.dictionary.get(code).map(|w| w.clone())
+    fn find_word(&self, code: usize) -> Option<Word<T>> {
+        self.dictionary.get(code).cloned()
+    }
+}
+
+impl Default for LzwDecoder {
+    fn default() -> Self {
+        LzwDecoder::new()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{EliasDeltaDecoder, EliasDeltaEncoder};
 
     #[test]
     fn lzw_decode_works() {
@@ -93,4 +125,41 @@ mod tests {
 
         assert_eq!(vec![0, 1, 0, 1, 0, 1, 0, 1, 0, 1], words);
     }
+
+    #[test]
+    fn alphabet_works() {
+        let mut lzw_decode_dict = LzwDecoder::with_alphabet(&[10u32, 20]);
+
+        let symbols = lzw_decode_dict.decode_text(&[0, 1, 2, 4]);
+
+        assert_eq!(vec![10, 20, 10, 20, 10, 20, 10], symbols);
+    }
+
+    #[test]
+    fn symbols_roundtrip_works() {
+        #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+        enum Opcode {
+            Push,
+            Add,
+            Jump,
+        }
+
+        let alphabet = [Opcode::Push, Opcode::Add, Opcode::Jump];
+        let program = [
+            Opcode::Push,
+            Opcode::Push,
+            Opcode::Add,
+            Opcode::Push,
+            Opcode::Push,
+            Opcode::Add,
+            Opcode::Jump,
+        ];
+
+        let bits = crate::encode_symbols::<_, EliasDeltaEncoder>(&program, &alphabet);
+
+        assert_eq!(
+            program.to_vec(),
+            crate::decode_symbols::<_, EliasDeltaDecoder>(&bits, &alphabet)
+        );
+    }
 }
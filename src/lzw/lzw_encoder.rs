@@ -1,87 +1,167 @@
 //! Things usefull for encoding LZW encoded data.
 
+use std::hash::Hash;
+
+use crate::lzw::trained_dictionary::TrainedDictionary;
 use crate::lzw::word::Word;
-use crate::lzw::{self, HashMapDictionary, ALPHABET_SIZE};
+use crate::lzw::{self, HashMapDictionary};
 
-/// Used to encode LZW encoded data.
-pub struct LzwEncoder {
-    dictionary: HashMapDictionary,
+/// Used to encode LZW encoded data. Symbols are bytes by default.
+pub struct LzwEncoder<T = u8> {
+    dictionary: HashMapDictionary<T>,
+    alphabet_size: usize,
     word_code: usize,
-    last_symbol: Option<u8>,
+    curr_word: Word<T>,
+    flushed_word: Option<Word<T>>,
 }
 
 impl LzwEncoder {
     /// Creates new instance of `LzwEncoder` with dictionary initialized
     /// to all ASCII symbols.
     pub fn new() -> Self {
+        LzwEncoder::with_alphabet(&lzw::byte_alphabet())
+    }
+
+    /// Creates new instance of `LzwEncoder` with dictionary initialized
+    /// to all ASCII symbols followed by words of trained `dictionary`.
+    pub fn with_dictionary(dictionary: &TrainedDictionary) -> Self {
+        let mut lzw_encoder = LzwEncoder::new();
+
+        for word in dictionary.words() {
+            lzw_encoder.add_word(word.clone());
+        }
+
+        lzw_encoder
+    }
+}
+
+impl<T> LzwEncoder<T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// Creates new instance of `LzwEncoder` with dictionary initialized
+    /// to symbols of `alphabet`, which get codes by their position.
+    pub fn with_alphabet(alphabet: &[T]) -> Self {
+        let dictionary = lzw::create_hashmap_dictionary(alphabet);
+
         LzwEncoder {
-            dictionary: lzw::create_hashmap_dictionary(),
-            word_code: ALPHABET_SIZE as usize + 1,
-            last_symbol: None,
+            alphabet_size: dictionary.len(),
+            word_code: dictionary.len(),
+            dictionary,
+            curr_word: Word::new(),
+            flushed_word: None,
         }
     }
 
     /// Encodes `symbols` using LZW encoding into `Vec<usize>`.
-    pub fn encode_text(&mut self, text: &[u8]) -> Vec<usize> {
-        let mut symbols = text.iter().map(|s| *s);
+    /// Encoder is flushed at the end, so all symbols are encoded.
+    pub fn encode_text(&mut self, text: &[T]) -> Vec<usize> {
+        let mut codes = self.encode(text);
+        codes.extend(self.flush());
 
-        let mut codes = vec![];
+        codes
+    }
 
-        while let Some(code) = self.get_next_code(&mut symbols) {
-            codes.push(code);
+    /// Encodes `symbols` without ending current word, so its code
+    /// is emitted by following calls or by `flush`.
+    pub fn encode(&mut self, text: &[T]) -> Vec<usize> {
+        text.iter()
+            .filter_map(|symbol| self.push_symbol(symbol.clone()))
+            .collect()
+    }
+
+    /// Ends current word and returns its code, so all symbols encoded so far
+    /// can be decoded. Dictionary is kept, so encoding can be continued.
+    pub fn flush(&mut self) -> Option<usize> {
+        if self.curr_word.is_empty() {
+            return None;
         }
 
-        codes
+        let curr_word = std::mem::take(&mut self.curr_word);
+        let code = self.get_word_code(&curr_word);
+
+        // Decoder adds flushed word with first symbol of the next word,
+        // so it's added the same way when next symbol comes.
+        self.flushed_word = Some(curr_word);
+
+        Some(code)
+    }
+
+    /// Returns number of words in dictionary, which is also
+    /// the code of next added word.
+    pub fn dictionary_size(&self) -> usize {
+        self.word_code
     }
 
-    /// Gets next code from `symbols` iterator and updates dictionary.
-    fn get_next_code<I>(&mut self, symbols: &mut I) -> Option<usize>
-    where
-        I: Iterator<Item = u8>,
-    {
-        let mut curr_word = Word::new();
+    /// Returns bound of codes emitted while encoding `text_len` more symbols.
+    /// Dictionary grows at most by one word per symbol, so every code is smaller.
+    pub fn code_bound(&self, text_len: usize) -> usize {
+        self.word_code.saturating_add(text_len)
+    }
+
+    /// Returns words added to initial dictionary in order of their codes.
+    pub fn added_words(&self) -> Vec<Word<T>> {
+        let mut words: Vec<_> = self
+            .dictionary
+            .iter()
+            .filter(|(_, &code)| code >= self.alphabet_size)
+            .collect();
 
-        match self.last_symbol {
-            Some(last_symbol) => curr_word.add_symbol(last_symbol),
-            None => curr_word.add_symbol(symbols.next()?),
+        words.sort_by_key(|(_, &code)| code);
+
+        words.into_iter().map(|(word, _)| word.clone()).collect()
+    }
+
+    /// Extends current word with `symbol`. When extended word isn't
+    /// in dictionary, it's added and code of current word is returned.
+    fn push_symbol(&mut self, symbol: T) -> Option<usize> {
+        if let Some(mut flushed_word) = self.flushed_word.take() {
+            flushed_word.add_symbol(symbol.clone());
+            self.add_word(flushed_word);
         }
 
-        while self.find_word(&curr_word).is_some() {
-            match symbols.next() {
-                Some(symbol) => curr_word.add_symbol(symbol),
-                None => return self.end_encoding(curr_word),
-            }
+        self.curr_word.add_symbol(symbol.clone());
+
+        if self.find_word(&self.curr_word).is_some() {
+            return None;
         }
 
-        let code = self.get_word_code(&curr_word.without_last_symbol());
+        assert!(self.curr_word.len() > 1, "symbol not in alphabet");
 
-        self.last_symbol = Some(curr_word.get_last_symbol());
-        self.dictionary.insert(curr_word, self.word_code);
-        self.word_code += 1;
+        let code = self.get_word_code(&self.curr_word.without_last_symbol());
+
+        let new_word = std::mem::replace(&mut self.curr_word, Word::from_vec(vec![symbol]));
+        self.add_word(new_word);
+
+        assert!(self.find_word(&self.curr_word).is_some(), "symbol not in alphabet");
 
         Some(code)
     }
 
-    // resets last symbol to end loop and returns last word code
-    fn end_encoding(&mut self, curr_word: Word) -> Option<usize> {
-        self.last_symbol = None;
-
-        Some(self.get_word_code(&curr_word))
+    fn add_word(&mut self, word: Word<T>) {
+        self.dictionary.insert(word, self.word_code);
+        self.word_code += 1;
     }
 
     // Make sure that word exists in dictionary !!!
-    fn get_word_code(&self, word: &Word) -> usize {
+    fn get_word_code(&self, word: &Word<T>) -> usize {
         let (_, code) = self.find_word(word).expect("word not in dictionary");
 
         code
     }
 
     // Finds word in the dictionary.
-    fn find_word(&self, word: &Word) -> Option<(&Word, usize)> {
+    fn find_word(&self, word: &Word<T>) -> Option<(&Word<T>, usize)> {
         self.dictionary.get_key_value(word).map(|(a, b)| (a, *b))
     }
 }
 
+impl Default for LzwEncoder {
+    fn default() -> Self {
+        LzwEncoder::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,14 +176,10 @@ mod tests {
     #[test]
     fn next_code_works() {
         let mut lzw_dict = LzwEncoder::new();
-        let symbols = [0, 1, 2];
-        let mut symbols_iter = symbols.into_iter();
-
-        let code = lzw_dict.get_next_code(&mut symbols_iter);
-        let code_two = lzw_dict.get_next_code(&mut symbols_iter);
 
-        assert_eq!(Some(0), code);
-        assert_eq!(Some(1), code_two);
+        assert_eq!(None, lzw_dict.push_symbol(0));
+        assert_eq!(Some(0), lzw_dict.push_symbol(1));
+        assert_eq!(Some(1), lzw_dict.push_symbol(2));
     }
 
     #[test]
@@ -115,4 +191,45 @@ mod tests {
 
         assert_eq!(vec![0, 1, 256, 258, 257, 1], codes);
     }
+
+    #[test]
+    fn flush_works() {
+        let mut lzw_dict = LzwEncoder::new();
+
+        assert_eq!(vec![0, 1], lzw_dict.encode(&[0, 1, 0, 1]));
+        assert_eq!(Some(256), lzw_dict.flush());
+        assert_eq!(None, lzw_dict.flush());
+
+        // Flushed word 0, 1 is added with 0 as word 258.
+        assert_eq!(vec![258], lzw_dict.encode_text(&[0, 1, 0]));
+        assert_eq!(259, lzw_dict.dictionary_size());
+    }
+
+    #[test]
+    fn code_bound_works() {
+        let mut lzw_dict = LzwEncoder::new();
+        let symbols: Vec<u8> = (0..=255).chain(0..=255).collect();
+
+        let bound = lzw_dict.code_bound(symbols.len());
+        let codes = lzw_dict.encode_text(&symbols);
+
+        assert!(codes.iter().all(|&code| code < bound));
+        assert_eq!(usize::MAX, lzw_dict.code_bound(usize::MAX));
+    }
+
+    #[test]
+    fn alphabet_works() {
+        let mut lzw_dict = LzwEncoder::with_alphabet(&["to", "be", "or", "not"]);
+
+        let codes = lzw_dict.encode_text(&["to", "be", "or", "not", "to", "be", "to", "be"]);
+
+        assert_eq!(vec![0, 1, 2, 3, 4, 4], codes);
+        assert_eq!(vec!["to", "be"], lzw_dict.added_words()[0].get_symbols_ref());
+    }
+
+    #[test]
+    #[should_panic(expected = "symbol not in alphabet")]
+    fn unknown_symbol_panics() {
+        LzwEncoder::with_alphabet(&[1u32, 2]).encode_text(&[1, 3]);
+    }
 }
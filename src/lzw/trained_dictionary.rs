@@ -0,0 +1,149 @@
+//! Things usefull for training LZW dictionary on sample data, so small
+//! inputs don't have to start from single byte words.
+
+use std::io;
+
+use crate::io_utils::{self, crc32, invalid_data, read_usize};
+use crate::lzw::lzw_encoder::LzwEncoder;
+use crate::lzw::word::Word;
+
+/// Bytes every serialized dictionary starts with.
+pub const MAGIC: [u8; 4] = *b"UCDI";
+
+/// Words added to initial LZW dictionary, in order of their codes.
+/// Every word without its last symbol is also in dictionary, so both
+/// encoder and decoder can continue growing it like they grew it themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrainedDictionary {
+    id: u32,
+    words: Vec<Word>,
+}
+
+impl TrainedDictionary {
+    /// Trains dictionary by encoding all `samples` with one `LzwEncoder`
+    /// and keeping first `max_words` words it added.
+    pub fn train(samples: &[&[u8]], max_words: usize) -> TrainedDictionary {
+        let mut lzw_encoder = LzwEncoder::new();
+
+        for sample in samples {
+            lzw_encoder.encode_text(sample);
+        }
+
+        let mut words = lzw_encoder.added_words();
+        words.truncate(max_words);
+
+        TrainedDictionary::from_words(words)
+    }
+
+    fn from_words(words: Vec<Word>) -> TrainedDictionary {
+        let mut dictionary = TrainedDictionary { id: 0, words };
+
+        let mut bytes = vec![];
+        dictionary.write_words(&mut bytes);
+
+        // Id 0 means that no dictionary was used.
+        dictionary.id = crc32(&bytes).max(1);
+
+        dictionary
+    }
+
+    /// Returns id identifying dictionary by its content.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns trained words in order of their codes.
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
+
+    pub fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&self.id.to_be_bytes());
+        self.write_words(bytes);
+    }
+
+    fn write_words(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.words.len().to_be_bytes());
+
+        for word in &self.words {
+            bytes.extend_from_slice(&word.len().to_be_bytes());
+            bytes.extend_from_slice(word.get_symbols_ref());
+        }
+    }
+
+    pub fn read(bytes: &[u8]) -> io::Result<TrainedDictionary> {
+        let mut bytes = bytes
+            .strip_prefix(&MAGIC)
+            .ok_or_else(|| invalid_data("file isn't LZW dictionary"))?;
+
+        let id = io_utils::read_u32(&mut bytes)?;
+        let words_count = read_usize(&mut bytes)?;
+
+        let words = (0..words_count)
+            .map(|_| {
+                let len = read_usize(&mut bytes)?;
+
+                Ok(Word::from_vec(io_utils::take(&mut bytes, len)?.to_vec()))
+            })
+            .collect::<io::Result<_>>()?;
+
+        let dictionary = TrainedDictionary::from_words(words);
+
+        match dictionary.id == id {
+            true => Ok(dictionary),
+            false => Err(invalid_data("LZW dictionary is corrupted")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EliasOmegaDecoder, EliasOmegaEncoder};
+
+    #[test]
+    fn train_works() {
+        let dictionary = TrainedDictionary::train(&[b"abab", b"abc"], 3);
+
+        let words: Vec<_> = dictionary.words().iter().map(|w| w.get_symbols_ref()).collect();
+
+        assert_eq!(vec![&b"ab"[..], b"ba", b"aba"], words);
+    }
+
+    #[test]
+    fn trained_roundtrip_works() {
+        let samples: [&[u8]; 2] = [
+            br#"{"id": 1, "name": "first", "tags": ["a", "b"]}"#,
+            br#"{"id": 2, "name": "second", "tags": ["c"]}"#,
+        ];
+        let dictionary = TrainedDictionary::train(&samples, 1000);
+
+        let message = br#"{"id": 3, "name": "third", "tags": ["a", "c"]}"#;
+
+        let trained = crate::encode_trained::<EliasOmegaEncoder>(message, &dictionary);
+        let plain = crate::encode::<EliasOmegaEncoder>(message);
+
+        assert!(trained.len() < plain.len());
+        assert_eq!(
+            message.to_vec(),
+            crate::decode_trained::<EliasOmegaDecoder>(&trained, &dictionary)
+        );
+    }
+
+    #[test]
+    fn write_read_works() {
+        let dictionary = TrainedDictionary::train(&[b"abracadabra"], 100);
+
+        let mut bytes = vec![];
+        dictionary.write(&mut bytes);
+
+        assert_eq!(dictionary, TrainedDictionary::read(&bytes).unwrap());
+
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+
+        assert!(TrainedDictionary::read(&bytes).is_err());
+        assert!(TrainedDictionary::read(&bytes[..10]).is_err());
+    }
+}
@@ -1,60 +1,77 @@
 //! `Word` structure usefull for LZW encoding and decoding.
 
-/// Represents words in ASCII format as `Vec<u8>`.
-#[derive(Hash, PartialEq, Eq, Clone)]
-pub struct Word {
-    symbols: Vec<u8>,
+/// Represents words as `Vec` of symbols, which are bytes by default.
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+pub struct Word<T = u8> {
+    symbols: Vec<T>,
 }
 
-impl Word {
+impl<T> Word<T>
+where
+    T: Clone,
+{
     /// Creates new empty instance of `Word`.
-    pub fn new() -> Word {
+    pub fn new() -> Word<T> {
         Word {
             symbols: Vec::new(),
         }
     }
 
-    /// Adds one ASCII symbol to the end of the `Word`.
-    pub fn add_symbol(&mut self, symbol: u8) {
+    /// Adds one symbol to the end of the `Word`.
+    pub fn add_symbol(&mut self, symbol: T) {
         self.symbols.push(symbol);
     }
 
-    /// Returns number of ASCII symbols in `Word`.
+    /// Returns number of symbols in `Word`.
     pub fn len(&self) -> usize {
         self.symbols.len()
     }
 
-    /// Returns last ASCII symbol from the `Word`.
-    pub fn get_last_symbol(&self) -> u8 {
-        *self.symbols.iter().last().expect("the word is empty")
+    /// Checks if `Word` has no symbols.
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
     }
-    
-    /// Return first ASCII symbol from the `Word`.
-    pub fn get_first_symbol(&self) -> u8 {
-        *self.symbols.get(0).expect("the word is empty")
+
+    /// Returns last symbol from the `Word`.
+    pub fn get_last_symbol(&self) -> T {
+        self.symbols.last().cloned().expect("the word is empty")
+    }
+
+    /// Return first symbol from the `Word`.
+    pub fn get_first_symbol(&self) -> T {
+        self.symbols.first().cloned().expect("the word is empty")
     }
 
-    /// Returns `Word`'s ASCII symbols as `Vec<u8>`.
+    /// Returns `Word`'s symbols as `Vec<T>`.
     /// Consumes `Word` so it can't be used later.
-    pub fn get_symbols(self) -> Vec<u8> {
+    pub fn get_symbols(self) -> Vec<T> {
         self.symbols
     }
 
-    /// Returns `Word`'s ASCII symbols as `&[u8]`.
-    pub fn get_symbols_ref(&self) -> &[u8] {
+    /// Returns `Word`'s symbols as `&[T]`.
+    pub fn get_symbols_ref(&self) -> &[T] {
         &self.symbols
     }
 
     /// Returns new `Word` which is this `Word` without last symbol.
-    pub fn without_last_symbol(&self) -> Word {
+    pub fn without_last_symbol(&self) -> Word<T> {
         Word {
             symbols: self.symbols[..self.symbols.len() - 1].to_vec(),
         }
     }
 
-    pub fn from_vec(symbols: Vec<u8>) -> Word {
+    pub fn from_vec(symbols: Vec<T>) -> Word<T> {
         Word {
             symbols,
         }
     }
 }
+
+impl<T> Default for Word<T>
+where
+    T: Clone,
+{
+    fn default() -> Self {
+        Word::new()
+    }
+}
@@ -1,64 +1,1385 @@
-use std::path::PathBuf;
-use structopt::StructOpt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use structopt::{clap, StructOpt};
 
+use universal_compressor::analysis;
+use universal_compressor::archive::{Archive, ArchiveWriter};
+use universal_compressor::header::{Header, MAX_INTEGER_WIDTH, MAX_TRANSFORMS};
+use universal_compressor::lzw::lzw_encoder::LzwEncoder;
+use universal_compressor::lzw::trained_dictionary::TrainedDictionary;
+use universal_compressor::number_encoders::{Codec, CODECS};
+use universal_compressor::report::CompressionStats;
+use universal_compressor::stats;
+use universal_compressor::transforms::{self, Transform};
 use universal_compressor::*;
 
+/// Extension added to compressed files when output isn't given.
+const EXTENSION: &str = "uc";
+
+/// All algorithms, position in this list is written in header as algorithm tag.
+const ALGORITHMS: [&str; 6] = ["lzw", "lz78", "bwt", "raw", "blocks", "stream"];
+
+/// Names of transforms accepted by '--transform'.
+const TRANSFORM_NAMES: [(&str, Transform); 5] = [
+    ("delta", Transform::Delta),
+    ("delta-of-delta", Transform::DeltaOfDelta),
+    ("zigzag", Transform::ZigZag),
+    ("xor", Transform::Xor),
+    ("frame-of-reference", Transform::FrameOfReference),
+];
+
+/// Encoding used by "fast" preset. Its tag is written in header,
+/// so decompression doesn't need to know about preset.
+const FAST_ENCODING: &str = "pfor";
+
+/// Highest order of file entropy printed with estimate.
+const ENTROPY_MAX_ORDER: usize = 2;
+
+/// Encodings which use dictionary size as the bound of every code,
+/// so they work only with lzw algorithm. Other encodings are registered
+/// codecs, which position in `CODECS` is written in header as encoding tag.
+const ADAPTIVE_ENCODINGS: [&str; 3] = ["arithmetic", "range", "truncated"];
+
+/// Tag of the first adaptive encoding, so registering new codecs
+/// doesn't change tags of adaptive ones.
+const ADAPTIVE_TAG_OFFSET: usize = 128;
+
 #[derive(StructOpt, Debug)]
 #[structopt(name = "universal_compressor")]
-struct Opt {
+enum Command {
+    /// Compresses file, header with used options is written in front of it.
+    Compress {
+        #[structopt(flatten)]
+        io: IoOpt,
+        #[structopt(short, long, default_value = "omega")]
+        encoding: String,
+        /// Defaults to lzw, or blocks with '--threads'.
+        #[structopt(short, long)]
+        algorithm: Option<String>,
+        #[structopt(long)]
+        estimate: bool,
+        #[structopt(short, long)]
+        threads: Option<usize>,
+        #[structopt(long, parse(try_from_str = parse_block_size))]
+        block_size: Option<usize>,
+        #[structopt(long, default_value = "text", possible_values = &["text", "json", "csv"])]
+        stats_format: String,
+        #[structopt(long, parse(from_os_str))]
+        dictionary: Option<PathBuf>,
+        #[structopt(long, parse(try_from_str = parse_transform))]
+        transform: Vec<Transform>,
+        /// Raw algorithm reads data as little endian integers of this many bytes.
+        #[structopt(long, parse(try_from_str = parse_integer_width))]
+        integer_width: Option<usize>,
+    },
+    /// Decompresses file using options stored in its header.
+    Decompress {
+        #[structopt(flatten)]
+        io: IoOpt,
+        #[structopt(short, long)]
+        threads: Option<usize>,
+        #[structopt(long)]
+        offset: Option<usize>,
+        #[structopt(long)]
+        length: Option<usize>,
+        #[structopt(long, parse(from_os_str))]
+        dictionary: Option<PathBuf>,
+    },
+    /// Prints header of compressed file without decoding it.
+    Info {
+        #[structopt(short, long, parse(from_os_str))]
+        file: Option<PathBuf>,
+    },
+    /// Decodes compressed file in memory and verifies its checksum.
+    Test {
+        #[structopt(short, long, parse(from_os_str))]
+        file: Option<PathBuf>,
+        #[structopt(long, parse(from_os_str))]
+        dictionary: Option<PathBuf>,
+    },
+    /// Trains LZW dictionary on sample files, so small files similar
+    /// to them can be compressed starting from it.
+    Train {
+        #[structopt(short, long, parse(from_os_str))]
+        output: PathBuf,
+        #[structopt(parse(from_os_str), required = true)]
+        samples: Vec<PathBuf>,
+        #[structopt(long, default_value = "8192")]
+        max_words: usize,
+    },
+    /// Compresses file with every encoding and prints ratio and speed.
+    Bench {
+        #[structopt(short, long, parse(from_os_str))]
+        file: Option<PathBuf>,
+        #[structopt(short, long, default_value = "lzw")]
+        algorithm: String,
+    },
+    /// Prints LZW phrase lengths, codes per dictionary size epoch,
+    /// code lengths of every encoding and the most frequent phrases.
+    Analyze {
+        #[structopt(short, long, parse(from_os_str))]
+        file: Option<PathBuf>,
+        #[structopt(long, default_value = "10")]
+        top: usize,
+    },
+    /// Packs files and directory trees into one archive.
+    Pack {
+        #[structopt(parse(from_os_str))]
+        archive: PathBuf,
+        #[structopt(parse(from_os_str), required = true)]
+        paths: Vec<PathBuf>,
+        #[structopt(short, long)]
+        encoding: Option<String>,
+    },
+    /// Lists members of archive.
+    List {
+        #[structopt(parse(from_os_str))]
+        archive: PathBuf,
+    },
+    /// Extracts all or chosen members of archive.
+    Extract {
+        #[structopt(parse(from_os_str))]
+        archive: PathBuf,
+        members: Vec<String>,
+        #[structopt(short = "C", long, parse(from_os_str), default_value = ".")]
+        destination: PathBuf,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+struct IoOpt {
     #[structopt(short, long, parse(from_os_str))]
-    file: PathBuf,
+    file: Option<PathBuf>,
     #[structopt(short, long, parse(from_os_str))]
-    output: PathBuf,
+    output: Option<PathBuf>,
     #[structopt(short, long)]
-    decode: bool,
-    #[structopt(short, long)]
-    encoding: Option<String>,
+    keep: bool,
+    /// Overwrites existing output file named after input file.
+    #[structopt(long)]
+    force: bool,
 }
 
 fn main() {
-    let opt = Opt::from_args();
+    match Command::from_args() {
+        Command::Compress {
+            io,
+            encoding,
+            algorithm,
+            estimate,
+            threads,
+            block_size,
+            stats_format,
+            dictionary,
+            transform,
+            integer_width,
+        } => {
+            let encoding = match encoding.as_str() {
+                "fast" => FAST_ENCODING,
+                encoding => encoding,
+            };
 
-    let data = std::fs::read(&opt.file).expect("file doesnt exist");
+            let blocks = algorithm.as_ref().is_none_or(|algorithm| algorithm == "blocks");
 
-    if opt.decode {
-        let (size_bytes, rest) = data.split_at(std::mem::size_of::<usize>());
-        let size = usize::from_be_bytes(size_bytes.try_into().unwrap());
-        let bytes = rest.to_vec();
+            if threads.is_some() && !blocks {
+                usage_error("--threads works only with blocks algorithm");
+            }
 
-        let bits = Bits::from_vec(size, bytes);
+            // Blocks are used by default whenever threads count is given.
+            let algorithm = algorithm.unwrap_or_else(|| match threads {
+                Some(_) => "blocks".to_string(),
+                None => "lzw".to_string(),
+            });
 
-        let decoded = match opt.encoding {
-            Some(e) if e == "fib" => universal_compressor::decode::<FibbonaciDecoder>(&bits),
-            Some(e) if e == "gamma" => universal_compressor::decode::<EliasGammaDecoder>(&bits),
-            Some(e) if e == "delta" => universal_compressor::decode::<EliasDeltaDecoder>(&bits),
-            _ => universal_compressor::decode::<EliasOmegaDecoder>(&bits),
-        };
+            let raw_numbers = RawNumbers {
+                integer_width: integer_width.unwrap_or(1),
+                transforms: &transform,
+            };
+
+            if let Err(message) =
+                check_compress_options(&algorithm, encoding, &transform, integer_width)
+            {
+                usage_error(&message);
+            }
+
+            if estimate && algorithm != "lzw" {
+                usage_error("--estimate works only with lzw algorithm");
+            }
+
+            let estimator = estimate.then(|| {
+                estimator(encoding).unwrap_or_else(|| {
+                    usage_error(&format!("--estimate doesn't work with {} encoding", encoding))
+                })
+            });
+
+            let input = Stream::input(&io.file);
+
+            // Piped data is compressed block by block, so it doesn't have to fit in memory.
+            let streamed = matches!(input, Stream::Std)
+                && algorithm == "lzw"
+                && dictionary.is_none();
+
+            if streamed && !estimate {
+                let output = Stream::output(&io, &input, false);
+                let block_size = block_size.unwrap_or(blocks::DEFAULT_BLOCK_SIZE);
+
+                let stats = compress_stream(&input, &output, encoding, block_size);
+
+                print_report(&stats, &stats_format, None, &output);
+                return;
+            }
+
+            let data = input.read();
+
+            if let Some(estimator) = estimator {
+                print_estimate(&data, estimator);
+                return;
+            }
+
+            let threads = threads.unwrap_or_else(default_threads);
+            let block_size = block_size.unwrap_or(blocks::DEFAULT_BLOCK_SIZE);
+
+            let dictionary = read_dictionary(&dictionary);
+
+            let (bytes, stats) = compress(
+                &data,
+                &algorithm,
+                encoding,
+                threads,
+                block_size,
+                dictionary.as_ref(),
+                &raw_numbers,
+            );
+
+            let output = Stream::output(&io, &input, false);
+            output.write(&bytes);
+
+            let entropies = (stats::entropy(&data), stats::entropy(&bytes));
+
+            print_report(&stats, &stats_format, Some(entropies), &output);
+            remove_source(&io, &input);
+        }
+        Command::Decompress {
+            io,
+            threads,
+            offset,
+            length,
+            dictionary,
+        } => {
+            let input = Stream::input(&io.file);
+            let output = Stream::output(&io, &input, true);
+
+            if offset.is_some() || length.is_some() {
+                output.write(&extract_range(&input, offset, length));
+                return;
+            }
+
+            let mut reader = input.reader();
+
+            let mut data = vec![0; Header::LEN];
+            reader.read_exact(&mut data).expect("couldn't read header");
+            let header = Header::read(&data).expect("couldn't read header");
+
+            if header_names(&header).0 == "stream" {
+                decompress_stream(&header, reader, &output);
+                remove_source(&io, &input);
+                return;
+            }
+
+            reader.read_to_end(&mut data).expect("couldn't read input");
+
+            let dictionary = read_dictionary(&dictionary);
+            let threads = threads.unwrap_or_else(default_threads);
+
+            let (header, decoded) = decompress(&data, threads, dictionary.as_ref());
+
+            if !header.verify(&decoded) {
+                panic!("checksum of decompressed data doesn't match");
+            }
+
+            output.write(&decoded);
+            remove_source(&io, &input);
+        }
+        Command::Info { file } => {
+            let data = Stream::input(&file).read();
+            let header = Header::read(&data).expect("couldn't read header");
+
+            // Ratio of empty file is undefined.
+            let compression_ratio = match header.data_len {
+                0 => "n/a".to_string(),
+                data_len => (data.len() as f64 / data_len as f64).to_string(),
+            };
+
+            let (algorithm, encoding) = header_names(&header);
 
-        std::fs::write(&opt.output, &decoded).expect("couldn't write output");
-    } else {
-        let encoded = match opt.encoding {
-            Some(e) if e == "fib" => universal_compressor::encode::<FibbonaciEncoder>(&data),
-            Some(e) if e == "gamma" => universal_compressor::encode::<EliasGammaEncoder>(&data),
-            Some(e) if e == "delta" => universal_compressor::encode::<EliasDeltaEncoder>(&data),
-            _ => universal_compressor::encode::<EliasOmegaEncoder>(&data),
+            println!("Algorithm: {}", algorithm);
+            println!("Encoding: {}", encoding);
+
+            let transforms: Vec<_> = header
+                .transform_list()
+                .into_iter()
+                .map(transform_name)
+                .collect();
+
+            if !transforms.is_empty() {
+                println!("Transforms: {}", transforms.join(", "));
+            }
+
+            if header.integer_width > 1 {
+                println!("Integer width (bytes): {}", header.integer_width);
+            }
+
+            // Length and checksum of stream are known only after decoding its blocks.
+            if algorithm == "stream" {
+                println!("Encoded file len (bytes): {}", data.len());
+                return;
+            }
+
+            println!("Decoded file len (bytes): {}", header.data_len);
+            println!("Encoded file len (bytes): {}", data.len());
+            println!("Compression ratio: {}", compression_ratio);
+            println!("Checksum (CRC-32): {:08x}", header.checksum);
+
+            match header.dictionary_id {
+                0 => println!("Dictionary: none"),
+                id => println!("Dictionary: {:08x}", id),
+            }
+        }
+        Command::Test { file, dictionary } => {
+            let data = Stream::input(&file).read();
+            let dictionary = read_dictionary(&dictionary);
+
+            let (header, decoded) = decompress(&data, default_threads(), dictionary.as_ref());
+
+            if !header.verify(&decoded) {
+                eprintln!("Checksum mismatch, file is corrupted");
+                std::process::exit(1);
+            }
+
+            println!("OK");
+        }
+        Command::Train {
+            output,
+            samples,
+            max_words,
+        } => {
+            let samples: Vec<_> = samples
+                .iter()
+                .map(|sample| std::fs::read(sample).expect("sample doesnt exist"))
+                .collect();
+            let samples: Vec<_> = samples.iter().map(Vec::as_slice).collect();
+
+            let dictionary = TrainedDictionary::train(&samples, max_words);
+
+            let mut bytes = vec![];
+            dictionary.write(&mut bytes);
+            std::fs::write(output, bytes).expect("couldn't write dictionary");
+
+            println!("Dictionary: {:08x}", dictionary.id());
+            println!("Trained words: {}", dictionary.words().len());
+        }
+        Command::Bench { file, algorithm } => {
+            let data = Stream::input(&file).read();
+
+            run_bench(&data, &algorithm);
+        }
+        Command::Analyze { file, top } => {
+            let data = Stream::input(&file).read();
+
+            print_analysis(&analysis::analyze(&data, top));
+        }
+        command => run_archive_command(command),
+    }
+}
+
+/// Input or output of the program, `-` stands for standard stream.
+enum Stream {
+    Std,
+    File(PathBuf),
+}
+
+impl Stream {
+    /// Reads from stdin when input file isn't given.
+    fn input(file: &Option<PathBuf>) -> Stream {
+        match file {
+            Some(file) if file != Path::new("-") => Stream::File(file.clone()),
+            _ => Stream::Std,
+        }
+    }
+
+    /// Without output file, output is named after input file by adding
+    /// or stripping extension, stdin input is written to stdout. Like gzip,
+    /// existing file with such name is overwritten only with `--force`.
+    fn output(io: &IoOpt, input: &Stream, decode: bool) -> Stream {
+        let output = match (&io.output, input) {
+            (Some(output), _) if output == Path::new("-") => return Stream::Std,
+            (Some(output), _) => return Stream::File(output.clone()),
+            (None, Stream::Std) => return Stream::Std,
+            (None, Stream::File(file)) if decode => {
+                if file.extension() != Some(EXTENSION.as_ref()) {
+                    panic!("input file has unknown suffix, expected .{}", EXTENSION);
+                }
+
+                file.with_extension("")
+            }
+            (None, Stream::File(file)) => {
+                let mut output = file.clone().into_os_string();
+                output.push(".");
+                output.push(EXTENSION);
+
+                output.into()
+            }
         };
 
-        let mut bytes = encoded.len().to_be_bytes().to_vec();
+        if output.exists() && !io.force {
+            panic!("{} already exists, use --force to overwrite it", output.display());
+        }
+
+        Stream::File(output)
+    }
+
+    fn reader(&self) -> Box<dyn Read> {
+        match self {
+            Stream::Std => Box::new(std::io::stdin().lock()),
+            Stream::File(file) => {
+                Box::new(BufReader::new(File::open(file).expect("file doesnt exist")))
+            }
+        }
+    }
+
+    fn writer(&self) -> Box<dyn Write> {
+        match self {
+            Stream::Std => Box::new(std::io::stdout().lock()),
+            Stream::File(file) => {
+                Box::new(BufWriter::new(File::create(file).expect("couldn't write output")))
+            }
+        }
+    }
+
+    fn read(&self) -> Vec<u8> {
+        match self {
+            Stream::Std => {
+                let mut data = vec![];
+
+                std::io::stdin()
+                    .lock()
+                    .read_to_end(&mut data)
+                    .expect("couldn't read stdin");
+
+                data
+            }
+            Stream::File(file) => std::fs::read(file).expect("file doesnt exist"),
+        }
+    }
+
+    fn write(&self, data: &[u8]) {
+        match self {
+            Stream::Std => {
+                let mut stdout = std::io::stdout().lock();
+
+                stdout.write_all(data).expect("couldn't write stdout");
+                stdout.flush().expect("couldn't write stdout");
+            }
+            Stream::File(file) => std::fs::write(file, data).expect("couldn't write output"),
+        }
+    }
+}
+
+/// Like gzip, source is removed only when output name wasn't given.
+fn remove_source(io: &IoOpt, input: &Stream) {
+    if let Stream::File(ref file) = input {
+        if io.output.is_none() && !io.keep {
+            std::fs::remove_file(file).expect("couldn't remove input file");
+        }
+    }
+}
+
+/// Parses `--block-size`, which has to be positive.
+fn parse_block_size(block_size: &str) -> Result<usize, String> {
+    match block_size.parse() {
+        Ok(0) => Err("block size has to be positive".to_string()),
+        Ok(block_size) => Ok(block_size),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// Parses `--integer-width`, which has to be from 1 to 8 bytes.
+fn parse_integer_width(width: &str) -> Result<usize, String> {
+    match width.parse() {
+        Ok(width @ 1..=MAX_INTEGER_WIDTH) => Ok(width),
+        Ok(_) => Err(format!("integer width has to be from 1 to {} bytes", MAX_INTEGER_WIDTH)),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// Parses `--transform` by its name.
+fn parse_transform(name: &str) -> Result<Transform, String> {
+    TRANSFORM_NAMES
+        .iter()
+        .find(|(transform_name, _)| *transform_name == name)
+        .map(|&(_, transform)| transform)
+        .ok_or_else(|| format!("unknown transform {}", name))
+}
+
+fn transform_name(transform: Transform) -> &'static str {
+    TRANSFORM_NAMES
+        .iter()
+        .find(|(_, t)| *t == transform)
+        .map(|&(name, _)| name)
+        .expect("all transforms are named")
+}
+
+/// Reports invalid combination of arguments the same way
+/// as argument parsing errors and exits.
+fn usage_error(message: &str) -> ! {
+    clap::Error::with_description(message, clap::ErrorKind::ArgumentConflict).exit()
+}
+
+fn default_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |threads| threads.get())
+}
+
+/// Prints `stats` as human readable text, single JSON object or CSV header
+/// followed by values row. `entropies` of input and output are missing
+/// when they were streamed, compression ratio when input is empty.
+fn print_report(
+    stats: &CompressionStats,
+    format: &str,
+    entropies: Option<(f64, f64)>,
+    output: &Stream,
+) {
+    let file_entropy = entropies.map(|(file_entropy, _)| file_entropy.to_string());
+    let compression_ratio = (stats.data_len > 0).then(|| stats.compression_ratio().to_string());
+    let code_entropy = entropies.map(|(_, code_entropy)| code_entropy.to_string());
+
+    // Key, text label and value (missing when it can't be computed).
+    let fields = [
+        ("data_len", "Encoded file len (bytes)", Some(stats.data_len.to_string())),
+        ("code_len", "Encoded code len (bytes)", Some(stats.code_bytes().to_string())),
+        ("compression_ratio", "Compression ratio", compression_ratio),
+        ("file_entropy", "Encoded file entropy", file_entropy),
+        ("code_entropy", "Encoded code entropy", code_entropy),
+        ("codes_count", "LZW codes count", Some(stats.codes_count.to_string())),
+        ("dictionary_size", "Final dictionary size", Some(stats.dictionary_size.to_string())),
+        ("average_code", "Average code value", Some(stats.average_code.to_string())),
+        ("bits_per_code", "Average bits per code", Some(stats.bits_per_code().to_string())),
+        ("codes_entropy", "LZW codes entropy", Some(stats.codes_entropy.to_string())),
+        (
+            "dictionary_time_ms",
+            "Dictionary stage time (ms)",
+            Some(as_millis(stats.dictionary_time)),
+        ),
+        (
+            "encoding_time_ms",
+            "Encoding stage time (ms)",
+            Some(as_millis(stats.encoding_time)),
+        ),
+    ];
+
+    let report = match format {
+        "json" => {
+            let values: Vec<_> = fields
+                .iter()
+                .map(|(key, _, value)| {
+                    format!("\"{}\":{}", key, value.as_deref().unwrap_or("null"))
+                })
+                .collect();
+
+            format!("{{{}}}\n", values.join(","))
+        }
+        "csv" => {
+            let keys: Vec<_> = fields.iter().map(|(key, _, _)| *key).collect();
+            let values: Vec<_> = fields
+                .iter()
+                .map(|(_, _, value)| value.as_deref().unwrap_or(""))
+                .collect();
+
+            format!("{}\n{}\n", keys.join(","), values.join(","))
+        }
+        _ => fields
+            .iter()
+            .filter_map(|(_, label, value)| Some(format!("{}: {}\n", label, value.as_ref()?)))
+            .collect(),
+    };
+
+    // Report can't be mixed with compressed data written to stdout.
+    match output {
+        Stream::Std => eprint!("{}", report),
+        Stream::File(_) => print!("{}", report),
+    }
+}
+
+fn as_millis(duration: Duration) -> String {
+    (duration.as_secs_f64() * 1000.0).to_string()
+}
+
+fn read_dictionary(path: &Option<PathBuf>) -> Option<TrainedDictionary> {
+    let bytes = std::fs::read(path.as_ref()?).expect("dictionary doesnt exist");
+
+    Some(TrainedDictionary::read(&bytes).expect("file isn't LZW dictionary"))
+}
+
+/// Returns tags of `algorithm` and `encoding` written in header.
+fn header_tags(algorithm: &str, encoding: &str) -> (u8, u8) {
+    let algorithm_tag = ALGORITHMS
+        .iter()
+        .position(|&a| a == algorithm)
+        .expect("unknown algorithm");
+    let encoding_tag = match ADAPTIVE_ENCODINGS.iter().position(|&e| e == encoding) {
+        Some(position) => ADAPTIVE_TAG_OFFSET + position,
+        None => CODECS
+            .iter()
+            .position(|codec| codec.name == encoding)
+            .expect("unknown encoding"),
+    };
+
+    (algorithm_tag as u8, encoding_tag as u8)
+}
+
+/// Returns the biggest number `algorithm` can pass to encoding when
+/// compressing `data_len` bytes, so too big numbers are found in advance.
+fn max_encoded_number(
+    algorithm: &str,
+    data_len: usize,
+    block_size: usize,
+    dictionary: Option<&TrainedDictionary>,
+) -> usize {
+    let lzw_encoder = dictionary.map_or_else(LzwEncoder::new, LzwEncoder::with_dictionary);
+
+    // Numbers are shifted by one, so they can be equal to the bound.
+    match algorithm {
+        "raw" | "bwt" => 256,
+        "blocks" | "stream" => lzw_encoder.code_bound(block_size.min(data_len)),
+        _ => lzw_encoder.code_bound(data_len),
+    }
+}
+
+/// Checks `max_number` fits into codec of `encoding`, e.g. simple9
+/// can't encode lzw codes of big files. Adaptive encodings fit any number.
+fn fits_encoding(encoding: &str, max_number: usize) -> bool {
+    number_encoders::find_codec(encoding).is_none_or(|codec| max_number <= codec.max_number)
+}
+
+fn check_max_number(encoding: &str, max_number: usize) {
+    if !fits_encoding(encoding, max_number) {
+        usage_error(&format!(
+            "{} encoding can't encode numbers up to {}, use smaller --block-size or other encoding",
+            encoding, max_number
+        ));
+    }
+}
+
+/// Checks that `algorithm`, `encoding`, `transforms` and `integer_width`
+/// are known and can be used together, before any data is read.
+fn check_compress_options(
+    algorithm: &str,
+    encoding: &str,
+    transforms: &[Transform],
+    integer_width: Option<usize>,
+) -> Result<(), String> {
+    if !ALGORITHMS.contains(&algorithm) {
+        return Err(format!("unknown algorithm {}", algorithm));
+    }
+
+    if !ADAPTIVE_ENCODINGS.contains(&encoding) && number_encoders::find_codec(encoding).is_none() {
+        return Err(format!("unknown encoding {}", encoding));
+    }
+
+    if !transforms.is_empty() && algorithm != "raw" {
+        return Err("--transform works only with raw algorithm".to_string());
+    }
+
+    if transforms.len() > MAX_TRANSFORMS {
+        return Err(format!("at most {} transforms can be used", MAX_TRANSFORMS));
+    }
+
+    if integer_width.is_some() && algorithm != "raw" {
+        return Err("--integer-width works only with raw algorithm".to_string());
+    }
+
+    if ADAPTIVE_ENCODINGS.contains(&encoding) && algorithm != "lzw" {
+        return Err(format!("{} encoding works only with lzw algorithm", encoding));
+    }
+
+    Ok(())
+}
+
+/// How raw algorithm reads data as numbers: little endian integers
+/// of `integer_width` bytes changed by `transforms` in order.
+struct RawNumbers<'a> {
+    integer_width: usize,
+    transforms: &'a [Transform],
+}
+
+impl RawNumbers<'_> {
+    /// Every byte is a number and isn't transformed.
+    const BYTES: RawNumbers<'static> = RawNumbers {
+        integer_width: 1,
+        transforms: &[],
+    };
+
+    /// Reads `data` as integers, the last one is padded by zeros.
+    fn read(&self, data: &[u8]) -> Vec<usize> {
+        data.chunks(self.integer_width)
+            .map(|chunk| {
+                let mut bytes = [0; MAX_INTEGER_WIDTH];
+                bytes[..chunk.len()].copy_from_slice(chunk);
+
+                u64::from_le_bytes(bytes) as usize
+            })
+            .collect()
+    }
+
+    /// Writes `numbers` back as integers without padding of the last one.
+    fn write(&self, numbers: &[usize], data_len: usize) -> Vec<u8> {
+        let mut data: Vec<_> = numbers
+            .iter()
+            .flat_map(|&number| (number as u64).to_le_bytes().into_iter().take(self.integer_width))
+            .collect();
+        data.truncate(data_len);
+
+        data
+    }
+}
+
+/// Returns the biggest number raw algorithm passes to encoding.
+fn max_transformed_number(data: &[u8], raw_numbers: &RawNumbers) -> usize {
+    let mut numbers = raw_numbers.read(data);
+
+    for &transform in raw_numbers.transforms {
+        transform.apply(&mut numbers);
+    }
+
+    // Numbers are shifted by one, because elias can't handle 0.
+    numbers.into_iter().max().map_or(0, |max| max.saturating_add(1))
+}
+
+/// Compresses `input` block by block with lzw into `output` without
+/// reading it whole. Length and checksum of data aren't known
+/// in advance, so every block has its own and header gets zeros.
+fn compress_stream(
+    input: &Stream,
+    output: &Stream,
+    encoding: &str,
+    block_size: usize,
+) -> CompressionStats {
+    let (algorithm_tag, encoding_tag) = header_tags("stream", encoding);
+    check_max_number(encoding, max_encoded_number("stream", usize::MAX, block_size, None));
+
+    let start = Instant::now();
+
+    let mut bytes = vec![];
+    Header::new(algorithm_tag, encoding_tag, &[]).write(&mut bytes);
+
+    let mut writer = output.writer();
+    writer.write_all(&bytes).expect("couldn't write output");
+
+    let (data_len, written) = stream::compress_stream(
+        &mut input.reader(),
+        &mut writer,
+        block_size,
+        |block| encode_lzw(block, encoding).0,
+    )
+    .expect("couldn't compress stream");
+
+    CompressionStats {
+        data_len,
+        code_len: (bytes.len() + written) * 8,
+        encoding_time: start.elapsed(),
+        ..Default::default()
+    }
+}
+
+/// Decompresses blocks following stream `header` and writes them
+/// to `output` one by one.
+fn decompress_stream(header: &Header, mut reader: Box<dyn Read>, output: &Stream) {
+    let (_, encoding) = header_names(header);
+
+    stream::decompress_stream(&mut reader, &mut output.writer(), |block| {
+        decode_lzw(block, encoding)
+    })
+    .expect("couldn't decompress stream");
+}
+
+/// Compresses `data` and writes header in front of it.
+/// Returns written bytes and statistics of compression.
+fn compress(
+    data: &[u8],
+    algorithm: &str,
+    encoding: &str,
+    threads: usize,
+    block_size: usize,
+    dictionary: Option<&TrainedDictionary>,
+    raw_numbers: &RawNumbers,
+) -> (Vec<u8>, CompressionStats) {
+    let (algorithm_tag, encoding_tag) = header_tags(algorithm, encoding);
+
+    let max_number = match algorithm {
+        "raw" => max_transformed_number(data, raw_numbers),
+        _ => max_encoded_number(algorithm, data.len(), block_size, dictionary),
+    };
+    check_max_number(encoding, max_number);
+
+    let start = Instant::now();
+
+    let (mut encoded, stats) = match (algorithm, dictionary) {
+        (_, Some(dictionary)) => encode_lzw_trained(data, algorithm, encoding, dictionary),
+        ("blocks", _) => {
+            basic_stats(data, encode_blocks(data, encoding, threads, block_size), start)
+        }
+        ("lz78", _) => basic_stats(data, write_bits(&encode_lz78(data, encoding)), start),
+        ("bwt", _) => basic_stats(data, write_bits(&encode_bwt(data, encoding)), start),
+        ("raw", _) => {
+            basic_stats(data, write_bits(&encode_raw(data, encoding, raw_numbers)), start)
+        }
+        ("stream", _) => {
+            let mut bytes = vec![];
+            let (_, written) = stream::compress_stream(&mut &data[..], &mut bytes, block_size, |block| {
+                encode_lzw(block, encoding).0
+            })
+            .expect("couldn't compress stream");
+
+            basic_stats(data, (bytes, written * 8), start)
+        }
+        _ => encode_lzw(data, encoding),
+    };
+
+    // Blocks of stream have their own lengths and checksums.
+    let header_data = match algorithm {
+        "stream" => &[],
+        _ => data,
+    };
+
+    let header = Header {
+        dictionary_id: dictionary.map_or(0, TrainedDictionary::id),
+        integer_width: raw_numbers.integer_width as u8,
+        ..Header::new(algorithm_tag, encoding_tag, header_data)
+    }
+    .with_transforms(raw_numbers.transforms);
+
+    let mut bytes = vec![];
+    header.write(&mut bytes);
+    bytes.append(&mut encoded);
+
+    (bytes, stats)
+}
+
+/// Only lzw algorithm reports statistics of its stages, the other
+/// ones get lengths and total time of encoding started at `start`.
+fn basic_stats(
+    data: &[u8],
+    (bytes, encoded_bits): (Vec<u8>, usize),
+    start: Instant,
+) -> (Vec<u8>, CompressionStats) {
+    let stats = CompressionStats {
+        data_len: data.len(),
+        code_len: encoded_bits,
+        encoding_time: start.elapsed(),
+        ..Default::default()
+    };
+
+    (bytes, stats)
+}
+
+/// Decompresses `data` with algorithm and encoding read from its header.
+/// `dictionary` has to be the one referenced by header, if any.
+fn decompress(
+    data: &[u8],
+    threads: usize,
+    dictionary: Option<&TrainedDictionary>,
+) -> (Header, Vec<u8>) {
+    let header = Header::read(data).expect("couldn't read header");
+    let data = &data[Header::LEN..];
+
+    let (algorithm, encoding) = header_names(&header);
+
+    if header.dictionary_id != 0 {
+        let dictionary = dictionary
+            .filter(|dictionary| dictionary.id() == header.dictionary_id)
+            .unwrap_or_else(|| panic!("file needs LZW dictionary {:08x}", header.dictionary_id));
+
+        return (header, decode_lzw_trained(data, encoding, dictionary));
+    }
+
+    let decoded = match algorithm {
+        "blocks" => decode_blocks(data, encoding, threads),
+        "lz78" => decode_lz78(data, encoding),
+        "bwt" => decode_bwt(data, encoding),
+        "raw" => {
+            let transforms = header.transform_list();
+            let raw_numbers = RawNumbers {
+                integer_width: header.integer_width as usize,
+                transforms: &transforms,
+            };
+
+            decode_raw(data, encoding, &raw_numbers, header.data_len)
+        }
+        "stream" => {
+            let mut decoded = vec![];
+
+            stream::decompress_stream(&mut &data[..], &mut decoded, |block| {
+                decode_lzw(block, encoding)
+            })
+            .expect("couldn't decompress stream");
+
+            // Blocks were verified one by one, so whole data is described after decoding.
+            let header = Header::new(header.algorithm, header.encoding, &decoded);
+
+            return (header, decoded);
+        }
+        _ => decode_lzw(data, encoding),
+    };
+
+    (header, decoded)
+}
+
+/// Returns names of algorithm and encoding stored in `header`.
+fn header_names(header: &Header) -> (&'static str, &'static str) {
+    let algorithm = ALGORITHMS
+        .get(header.algorithm as usize)
+        .expect("unknown algorithm tag");
+    let encoding_tag = header.encoding as usize;
+    let encoding = match encoding_tag.checked_sub(ADAPTIVE_TAG_OFFSET) {
+        Some(position) => ADAPTIVE_ENCODINGS.get(position).copied(),
+        None => CODECS.get(encoding_tag).map(|codec| codec.name),
+    }
+    .expect("unknown encoding tag");
+
+    (algorithm, encoding)
+}
+
+/// Compresses and decompresses `data` with every encoding supported
+/// by `algorithm` and prints their ratio and speed.
+fn run_bench(data: &[u8], algorithm: &str) {
+    let threads = default_threads();
+    let megabytes = data.len() as f64 / 1_000_000.0;
+
+    println!(
+        "{:<12} {:>12} {:>10} {:>15} {:>17}",
+        "encoding", "code (bytes)", "ratio", "compress (MB/s)", "decompress (MB/s)"
+    );
+
+    let encodings = CODECS.iter().map(|codec| codec.name).chain(ADAPTIVE_ENCODINGS);
+
+    for encoding in encodings {
+        if ADAPTIVE_ENCODINGS.contains(&encoding) && algorithm != "lzw" {
+            continue;
+        }
+
+        let block_size = blocks::DEFAULT_BLOCK_SIZE;
+
+        if !fits_encoding(encoding, max_encoded_number(algorithm, data.len(), block_size, None)) {
+            continue;
+        }
 
-        bytes.append(&mut encoded.get_bits().to_vec());
+        let start = Instant::now();
+        let (bytes, stats) =
+            compress(data, algorithm, encoding, threads, block_size, None, &RawNumbers::BYTES);
+        let compress_time = start.elapsed().as_secs_f64();
 
-        std::fs::write(&opt.output, bytes).expect("couldn't write output");
+        let start = Instant::now();
+        let (header, decoded) = decompress(&bytes, threads, None);
+        let decompress_time = start.elapsed().as_secs_f64();
 
-        let data_len = data.len();
-        let encoded_len = encoded.len() / 8;
+        if !header.verify(&decoded) {
+            panic!("{} encoding round-trip failed", encoding);
+        }
 
-        let compression_ratio = encoded_len as f64 / data_len as f64;
+        println!(
+            "{:<12} {:>12} {:>10.4} {:>15.2} {:>17.2}",
+            encoding,
+            stats.code_bytes(),
+            stats.compression_ratio(),
+            megabytes / compress_time,
+            megabytes / decompress_time
+        );
+    }
+}
+
+fn print_analysis(analysis: &analysis::Analysis) {
+    println!("Phrase lengths:");
+    println!("{:>8} {:>10}", "length", "count");
+
+    for (length, count) in &analysis.phrase_lengths {
+        println!("{:>8} {:>10}", length, count);
+    }
+
+    println!();
+    println!("Dictionary size epochs:");
+    println!("{:>8} {:>10} {:>14}", "bits", "codes", "average code");
+
+    for epoch in &analysis.epochs {
+        println!(
+            "{:>8} {:>10} {:>14.2}",
+            epoch.dictionary_bits, epoch.codes_count, epoch.average_code
+        );
+    }
+
+    let histograms = [
+        ("omega", analysis::code_len_histogram::<EliasOmegaEncoder>(&analysis.codes)),
+        ("gamma", analysis::code_len_histogram::<EliasGammaEncoder>(&analysis.codes)),
+        ("delta", analysis::code_len_histogram::<EliasDeltaEncoder>(&analysis.codes)),
+        ("fib", analysis::code_len_histogram::<FibbonaciEncoder>(&analysis.codes)),
+    ];
+
+    let mut lengths: Vec<_> = histograms.iter().flat_map(|(_, h)| h.keys()).collect();
+    lengths.sort();
+    lengths.dedup();
 
-        println!("Encoded file len (bytes): {}", data_len);
-        println!("Encoded code len (bytes): {}", encoded_len);
-        println!("Compression ratio: {}", compression_ratio);
-        println!("Encoded file entropy: {}", entropy_calculator::get_file_entropy(&opt.file));
-        println!("Encoded code entropy: {}", entropy_calculator::get_file_entropy(&opt.output));
+    println!();
+    println!("Codes of every length (bits):");
+    print!("{:>8}", "bits");
+
+    for (name, _) in &histograms {
+        print!(" {:>10}", name);
+    }
+
+    println!();
+
+    for length in lengths {
+        print!("{:>8}", length);
+
+        for (_, histogram) in &histograms {
+            print!(" {:>10}", histogram.get(length).unwrap_or(&0));
+        }
+
+        println!();
     }
+
+    print!("{:>8}", "total");
+
+    for (_, histogram) in &histograms {
+        let total: usize = histogram.iter().map(|(length, count)| length * count).sum();
+        print!(" {:>10}", total);
+    }
+
+    println!();
+    println!();
+    println!("Most frequent phrases:");
+    println!("{:>10} phrase", "count");
+
+    for (phrase, count) in &analysis.top_phrases {
+        println!("{:>10} {:?}", count, String::from_utf8_lossy(phrase));
+    }
+}
+
+/// Prints lzw code length predicted without encoding `data`
+/// together with entropy bounds of `data` and lzw codes.
+fn print_estimate(data: &[u8], estimator: Estimator) {
+    let (encoded_size, efficiency) = estimator(data);
+
+    let data_len = data.len();
+    let encoded_len = encoded_size / 8;
+
+    let compression_ratio = match data_len {
+        0 => "n/a".to_string(),
+        data_len => (encoded_len as f64 / data_len as f64).to_string(),
+    };
+
+    println!("Encoded file len (bytes): {}", data_len);
+    println!("Estimated code len (bytes): {}", encoded_len);
+    println!("Estimated compression ratio: {}", compression_ratio);
+
+    for order in 0..=ENTROPY_MAX_ORDER {
+        let entropy = stats::conditional_entropy(data, order);
+        println!("Order-{} file entropy: {}", order, entropy);
+    }
+
+    println!("LZW codes count: {}", efficiency.codes_count);
+    println!("Average code len (bits): {}", efficiency.average_code_len);
+    println!("LZW codes entropy: {}", efficiency.entropy);
+    println!("Redundancy (bits per code): {}", efficiency.redundancy());
+}
+
+/// Computes encoded size of lzw code and efficiency of its codes.
+type Estimator = fn(&[u8]) -> (usize, stats::CodeEfficiency);
+
+/// Returns estimator of `encoding`, only encodings
+/// with `CodeLength` can be estimated.
+fn estimator(encoding: &str) -> Option<Estimator> {
+    match encoding {
+        "omega" => Some(estimate::<EliasOmegaEncoder>),
+        "gamma" => Some(estimate::<EliasGammaEncoder>),
+        "delta" => Some(estimate::<EliasDeltaEncoder>),
+        "fib" => Some(estimate::<FibbonaciEncoder>),
+        _ => None,
+    }
+}
+
+fn estimate<E>(data: &[u8]) -> (usize, stats::CodeEfficiency)
+where
+    E: CodeLength,
+{
+    (
+        universal_compressor::encoded_size::<E>(data),
+        stats::lzw_code_efficiency::<E>(data),
+    )
+}
+
+/// Returns written bytes of lzw code and statistics of its stages.
+fn encode_lzw(data: &[u8], encoding: &str) -> (Vec<u8>, CompressionStats) {
+    let (encoded, stats) = match encoding {
+        "arithmetic" => {
+            universal_compressor::encode_adaptive_with_stats::<ArithmeticEncoder>(data)
+        }
+        "range" => universal_compressor::encode_adaptive_with_stats::<RangeEncoder>(data),
+        "truncated" => {
+            universal_compressor::encode_adaptive_with_stats::<TruncatedBinaryEncoder>(data)
+        }
+        _ => universal_compressor::encode_with_stats(data, codec(encoding).encode),
+    };
+
+    (write_bits(&[encoded]).0, stats)
+}
+
+fn decode_lzw(data: &[u8], encoding: &str) -> Vec<u8> {
+    let bits = read_bits(data, 1).remove(0);
+
+    match encoding {
+        "arithmetic" => universal_compressor::decode_adaptive::<ArithmeticDecoder>(&bits),
+        "range" => universal_compressor::decode_adaptive::<RangeDecoder>(&bits),
+        "truncated" => universal_compressor::decode_adaptive::<TruncatedBinaryDecoder>(&bits),
+        _ => universal_compressor::decode_with(&bits, codec(encoding).decode),
+    }
+}
+
+/// Finds codec of `encoding`, which isn't an adaptive one.
+fn codec(encoding: &str) -> &'static Codec {
+    number_encoders::find_codec(encoding).expect("unknown encoding")
+}
+
+/// Trained dictionary works only with lzw algorithm and
+/// registered encodings, which can be chosen at runtime.
+fn encode_lzw_trained(
+    data: &[u8],
+    algorithm: &str,
+    encoding: &str,
+    dictionary: &TrainedDictionary,
+) -> (Vec<u8>, CompressionStats) {
+    if algorithm != "lzw" {
+        panic!("dictionary works only with lzw algorithm");
+    }
+
+    let codec = number_encoders::find_codec(encoding).expect("encoding doesn't work with dictionary");
+
+    let (encoded, stats) =
+        universal_compressor::encode_trained_with_stats(data, codec.encode, dictionary);
+
+    (write_bits(&[encoded]).0, stats)
+}
+
+fn decode_lzw_trained(data: &[u8], encoding: &str, dictionary: &TrainedDictionary) -> Vec<u8> {
+    let bits = read_bits(data, 1).remove(0);
+    let codec = number_encoders::find_codec(encoding).expect("encoding doesn't work with dictionary");
+
+    universal_compressor::decode_trained_with(&bits, codec.decode, dictionary)
+}
+
+// Symbols are always written as raw bytes, only indices use `encoding`.
+fn encode_lz78(data: &[u8], encoding: &str) -> Vec<Bits> {
+    let (indices, symbols) =
+        universal_compressor::encode_lz78_with(data, codec(encoding).encode, RawByteEncoder::encode);
+
+    vec![indices, symbols]
+}
+
+fn decode_lz78(data: &[u8], encoding: &str) -> Vec<u8> {
+    let mut streams = read_bits(data, 2);
+    let symbols = streams.pop().unwrap();
+    let indices = streams.pop().unwrap();
+
+    universal_compressor::decode_lz78_with(
+        &indices,
+        &symbols,
+        codec(encoding).decode,
+        RawByteDecoder::decode,
+    )
+}
+
+fn encode_bwt(data: &[u8], encoding: &str) -> Vec<Bits> {
+    vec![universal_compressor::encode_bwt_with(data, codec(encoding).encode)]
+}
+
+fn decode_bwt(data: &[u8], encoding: &str) -> Vec<u8> {
+    let bits = read_bits(data, 1).remove(0);
+
+    universal_compressor::decode_bwt_with(&bits, codec(encoding).decode)
+}
+
+// Bytes are encoded as numbers after `transforms`, which are written in header.
+fn encode_raw(data: &[u8], encoding: &str, raw_numbers: &RawNumbers) -> Vec<Bits> {
+    let numbers = raw_numbers.read(data);
+    let encode = codec(encoding).encode;
+
+    let encoded = transforms::encode_with_transforms(&numbers, raw_numbers.transforms, encode)
+        .unwrap_or_else(|error| panic!("{}", error));
+
+    vec![encoded]
+}
+
+fn decode_raw(data: &[u8], encoding: &str, raw_numbers: &RawNumbers, data_len: usize) -> Vec<u8> {
+    let bits = read_bits(data, 1).remove(0);
+    let decode = codec(encoding).decode;
+
+    let numbers = transforms::decode_with_transforms(&bits, raw_numbers.transforms, decode);
+
+    raw_numbers.write(&numbers, data_len)
+}
+
+// Blocks are always compressed with lzw algorithm.
+fn encode_blocks(
+    data: &[u8],
+    encoding: &str,
+    threads: usize,
+    block_size: usize,
+) -> (Vec<u8>, usize) {
+    let blocks = blocks::compress_blocks_with(data, block_size, threads, codec(encoding).encode);
+
+    let encoded_bits = blocks.iter().map(|block| block.bits.len()).sum();
+
+    (blocks::write_blocks(&blocks), encoded_bits)
+}
+
+fn decode_blocks(data: &[u8], encoding: &str, threads: usize) -> Vec<u8> {
+    let blocks = blocks::read_blocks(data).expect("blocks index is corrupted");
+
+    blocks::decompress_blocks_with(&blocks, threads, codec(encoding).decode)
+}
+
+/// Decodes only blocks covering range of `length` bytes starting at `offset`.
+fn extract_range(input: &Stream, offset: Option<usize>, length: Option<usize>) -> Vec<u8> {
+    let file = match input {
+        Stream::File(file) => file,
+        Stream::Std => panic!("range can be extracted only from file"),
+    };
+
+    let mut file = File::open(file).expect("file doesnt exist");
+
+    let mut header = [0; Header::LEN];
+    file.read_exact(&mut header).expect("couldn't read header");
+    let header = Header::read(&header).expect("couldn't read header");
+
+    let (algorithm, encoding) = header_names(&header);
+
+    if algorithm != "blocks" {
+        panic!("range can be extracted only from file compressed in blocks");
+    }
+
+    read_range(file, codec(encoding).decode, offset, length)
+}
+
+fn read_range(
+    file: File,
+    decode: fn(&Bits) -> Vec<usize>,
+    offset: Option<usize>,
+    length: Option<usize>,
+) -> Vec<u8> {
+    let mut decompressor = seekable::SeekableDecompressor::with_decode(file, decode)
+        .expect("file isn't blocks container");
+
+    let offset = offset.unwrap_or(0);
+    let length = length.unwrap_or(decompressor.len());
+
+    let mut range = vec![];
+
+    decompressor
+        .seek(SeekFrom::Start(offset as u64))
+        .expect("couldn't seek to offset");
+    decompressor
+        .take(length as u64)
+        .read_to_end(&mut range)
+        .expect("couldn't decode range");
+
+    range
+}
+
+fn run_archive_command(command: Command) {
+    match command {
+        Command::Pack {
+            archive,
+            paths,
+            encoding,
+        } => {
+            let codec_name = encoding.as_deref().unwrap_or("omega");
+            let codec_tag = number_encoders::CODECS
+                .iter()
+                .position(|codec| codec.name == codec_name)
+                .expect("unknown encoding");
+
+            let file = File::create(archive).expect("couldn't create archive");
+            let mut writer = ArchiveWriter::new(std::io::BufWriter::new(file), codec_tag)
+                .expect("couldn't write archive");
+
+            for path in paths {
+                writer.add_path(&path).expect("couldn't add path to archive");
+            }
+
+            writer.finish().expect("couldn't write archive");
+        }
+        Command::List { archive } => {
+            let archive = open_archive(&archive);
+
+            println!("Encoding: {}", archive.codec_name());
+
+            for entry in archive.entries() {
+                println!(
+                    "{} {:o} {:>12} {:>12} {}",
+                    if entry.is_dir { 'd' } else { '-' },
+                    entry.mode,
+                    entry.size,
+                    entry.mtime,
+                    entry.path
+                );
+            }
+        }
+        Command::Extract {
+            archive,
+            members,
+            destination,
+        } => {
+            open_archive(&archive)
+                .extract(&destination, &members)
+                .expect("couldn't extract archive");
+        }
+        _ => unreachable!("not an archive command"),
+    }
+}
+
+fn open_archive(path: &PathBuf) -> Archive<File> {
+    let file = File::open(path).expect("archive doesnt exist");
+
+    Archive::new(file).expect("file isn't an archive")
+}
+
+/// Writes sizes of all bit streams followed by their bytes.
+/// Returns written bytes and summed length of streams.
+fn write_bits(encoded: &[Bits]) -> (Vec<u8>, usize) {
+    let mut bytes = vec![];
+
+    for bits in encoded {
+        bytes.append(&mut bits.len().to_be_bytes().to_vec());
+    }
+
+    for bits in encoded {
+        bytes.append(&mut bits.get_bits().to_vec());
+    }
+
+    (bytes, encoded.iter().map(|bits| bits.len()).sum())
+}
+
+/// Reads `count` bit streams written as their sizes followed by their bytes.
+fn read_bits(data: &[u8], count: usize) -> Vec<Bits> {
+    let (sizes, mut rest) = data.split_at(count * std::mem::size_of::<usize>());
+
+    sizes
+        .chunks(std::mem::size_of::<usize>())
+        .map(|size_bytes| {
+            let size = usize::from_be_bytes(size_bytes.try_into().unwrap());
+            let (bytes, tail) = rest.split_at(size.div_ceil(8));
+            rest = tail;
+
+            Bits::from_vec(size, bytes.to_vec())
+        })
+        .collect()
 }
@@ -0,0 +1,140 @@
+//! Things usefull for compressing stream of small messages with one
+//! LZW dictionary, which keeps growing across all of them.
+
+use std::marker::PhantomData;
+
+use crate::bits::Bits;
+use crate::lzw::lzw_decoder::LzwDecoder;
+use crate::lzw::lzw_encoder::LzwEncoder;
+use crate::lzw::trained_dictionary::TrainedDictionary;
+use crate::number_encoders::{NumberDecoder, NumberEncoder};
+
+/// Encodes messages one by one. Every message is flushed, so it can be
+/// decoded as soon as it's received, but dictionary is kept for the next ones.
+pub struct MessageEncoder<E> {
+    lzw_encoder: LzwEncoder,
+    encoder: PhantomData<E>,
+}
+
+impl<E> MessageEncoder<E>
+where
+    E: NumberEncoder,
+{
+    pub fn new() -> MessageEncoder<E> {
+        MessageEncoder::with_lzw_encoder(LzwEncoder::new())
+    }
+
+    /// Creates new instance of `MessageEncoder` starting from trained `dictionary`.
+    pub fn with_dictionary(dictionary: &TrainedDictionary) -> MessageEncoder<E> {
+        MessageEncoder::with_lzw_encoder(LzwEncoder::with_dictionary(dictionary))
+    }
+
+    fn with_lzw_encoder(lzw_encoder: LzwEncoder) -> MessageEncoder<E> {
+        MessageEncoder {
+            lzw_encoder,
+            encoder: PhantomData,
+        }
+    }
+
+    /// Encodes `message`. Messages have to be decoded in the same order.
+    pub fn encode_message(&mut self, message: &[u8]) -> Bits {
+        let codes = self.lzw_encoder.encode_text(message);
+
+        // Needed because elias can't handle 0.
+        let numbers: Vec<_> = codes.iter().map(|code| code + 1).collect();
+
+        E::encode(&numbers)
+    }
+}
+
+impl<E> Default for MessageEncoder<E>
+where
+    E: NumberEncoder,
+{
+    fn default() -> Self {
+        MessageEncoder::new()
+    }
+}
+
+/// Decodes messages encoded by `MessageEncoder` in order they were encoded.
+pub struct MessageDecoder<D> {
+    lzw_decoder: LzwDecoder,
+    decoder: PhantomData<D>,
+}
+
+impl<D> MessageDecoder<D>
+where
+    D: NumberDecoder,
+{
+    pub fn new() -> MessageDecoder<D> {
+        MessageDecoder::with_lzw_decoder(LzwDecoder::new())
+    }
+
+    /// Creates new instance of `MessageDecoder` starting from trained `dictionary`.
+    pub fn with_dictionary(dictionary: &TrainedDictionary) -> MessageDecoder<D> {
+        MessageDecoder::with_lzw_decoder(LzwDecoder::with_dictionary(dictionary))
+    }
+
+    fn with_lzw_decoder(lzw_decoder: LzwDecoder) -> MessageDecoder<D> {
+        MessageDecoder {
+            lzw_decoder,
+            decoder: PhantomData,
+        }
+    }
+
+    pub fn decode_message(&mut self, message: &Bits) -> Vec<u8> {
+        // Needed because elias can't handle 0.
+        let codes: Vec<_> = D::decode(message).into_iter().map(|n| n - 1).collect();
+
+        self.lzw_decoder.decode_text(&codes)
+    }
+}
+
+impl<D> Default for MessageDecoder<D>
+where
+    D: NumberDecoder,
+{
+    fn default() -> Self {
+        MessageDecoder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EliasOmegaDecoder, EliasOmegaEncoder};
+
+    #[test]
+    fn messages_roundtrip_works() {
+        let messages: [&[u8]; 4] = [
+            br#"{"id": 1, "status": "ok"}"#,
+            br#"{"id": 2, "status": "ok"}"#,
+            b"a",
+            br#"{"id": 3, "status": "ok"}"#,
+        ];
+
+        let mut encoder = MessageEncoder::<EliasOmegaEncoder>::new();
+        let mut decoder = MessageDecoder::<EliasOmegaDecoder>::new();
+
+        let encoded: Vec<_> = messages.iter().map(|m| encoder.encode_message(m)).collect();
+
+        for (message, bits) in messages.iter().zip(&encoded) {
+            assert_eq!(message.to_vec(), decoder.decode_message(bits));
+        }
+
+        // Later message reuses words from the first one.
+        assert!(encoded[3].len() < encoded[0].len());
+    }
+
+    #[test]
+    fn repeated_symbol_across_messages_works() {
+        let mut encoder = MessageEncoder::<EliasOmegaEncoder>::new();
+        let mut decoder = MessageDecoder::<EliasOmegaDecoder>::new();
+
+        for message in [&b"a"[..], b"aaaa", b"", b"ab", b"aaaaaa"] {
+            let bits = encoder.encode_message(message);
+
+            assert_eq!(message.to_vec(), decoder.decode_message(&bits));
+        }
+    }
+}
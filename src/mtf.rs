@@ -0,0 +1,76 @@
+//! Things usefull for move-to-front transform.
+
+/// Keeps list of symbols ordered by their last usage.
+pub struct MoveToFront {
+    symbols: Vec<u8>,
+}
+
+impl MoveToFront {
+    /// Creates new instance of `MoveToFront` with all ASCII symbols
+    /// in ascending order.
+    pub fn new() -> MoveToFront {
+        MoveToFront {
+            symbols: (0..=255).collect(),
+        }
+    }
+
+    /// Transforms `text` into positions of its symbols in the list.
+    pub fn encode_text(&mut self, text: &[u8]) -> Vec<usize> {
+        text.iter()
+            .map(|&symbol| {
+                let index = self
+                    .symbols
+                    .iter()
+                    .position(|&s| s == symbol)
+                    .expect("every symbol is in the list");
+
+                self.move_to_front(index);
+
+                index
+            })
+            .collect()
+    }
+
+    /// Transforms positions in the list back into symbols.
+    pub fn decode_text(&mut self, indices: &[usize]) -> Vec<u8> {
+        indices
+            .iter()
+            .map(|&index| {
+                let symbol = self.symbols[index];
+
+                self.move_to_front(index);
+
+                symbol
+            })
+            .collect()
+    }
+
+    fn move_to_front(&mut self, index: usize) {
+        self.symbols[..=index].rotate_right(1);
+    }
+}
+
+impl Default for MoveToFront {
+    fn default() -> Self {
+        MoveToFront::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mtf_encode_works() {
+        let indices = MoveToFront::new().encode_text(&[1, 1, 0, 2, 2, 1]);
+
+        assert_eq!(vec![1, 0, 1, 2, 0, 2], indices);
+    }
+
+    #[test]
+    fn mtf_decode_works() {
+        let text = MoveToFront::new().decode_text(&[1, 0, 1, 2, 0, 2]);
+
+        assert_eq!(vec![1, 1, 0, 2, 2, 1], text);
+    }
+}
@@ -1,10 +1,31 @@
 //! Traits and structs for numbers encoding and decoding.
 
 pub mod elias;
+pub mod arithmetic;
+pub mod auto;
+pub mod elias_fano;
 pub mod fibbonaci;
+pub mod huffman;
+pub mod interpolative;
+pub mod pfor;
+pub mod range;
+pub mod rans;
+pub mod raw;
+pub mod simple;
+pub mod truncated_binary;
 
 use crate::Bits;
 
+use auto::{AutoDecoder, AutoEncoder};
+use elias::delta::{EliasDeltaDecoder, EliasDeltaEncoder};
+use elias::gamma::{EliasGammaDecoder, EliasGammaEncoder};
+use elias::omega::{EliasOmegaDecoder, EliasOmegaEncoder};
+use fibbonaci::{FibbonaciDecoder, FibbonaciEncoder};
+use huffman::{HuffmanDecoder, HuffmanEncoder};
+use pfor::{PForDeltaDecoder, PForDeltaEncoder};
+use rans::{RansDecoder, RansEncoder};
+use simple::{Simple16Decoder, Simple16Encoder, Simple9Decoder, Simple9Encoder, SIMPLE_MAX_NUMBER};
+
 // TODO: change traits to take self as argument so structs
 // can keep track of encoding/decoding in their fields.
 
@@ -16,4 +37,112 @@ pub trait NumberEncoder {
 /// Trait used for decoding `usize` numbers.
 pub trait NumberDecoder {
     fn decode(bits: &Bits) -> Vec<usize>;
+
+    /// Decodes only the first `count` numbers. Decoders reading numbers
+    /// one by one stop after them, the other ones decode all numbers.
+    fn decode_prefix(bits: &Bits, count: usize) -> Vec<usize> {
+        let mut numbers = Self::decode(bits);
+        numbers.truncate(count);
+        numbers
+    }
+}
+
+/// Trait used for computing length of number's code without encoding it.
+pub trait CodeLength {
+    fn code_len(number: usize) -> usize;
+}
+
+/// Encoder and decoder pair which can be chosen at runtime.
+pub struct Codec {
+    pub name: &'static str,
+    pub encode: fn(&[usize]) -> Bits,
+    pub decode: fn(&Bits) -> Vec<usize>,
+    /// The biggest number codec can encode.
+    pub max_number: usize,
+}
+
+/// All registered codecs. Position in this list is used as codec tag,
+/// so new codecs have to be added at the end.
+pub const CODECS: [Codec; 10] = [
+    Codec {
+        name: "omega",
+        encode: EliasOmegaEncoder::encode,
+        decode: EliasOmegaDecoder::decode,
+        max_number: usize::MAX,
+    },
+    Codec {
+        name: "gamma",
+        encode: EliasGammaEncoder::encode,
+        decode: EliasGammaDecoder::decode,
+        max_number: usize::MAX,
+    },
+    Codec {
+        name: "delta",
+        encode: EliasDeltaEncoder::encode,
+        decode: EliasDeltaDecoder::decode,
+        max_number: usize::MAX,
+    },
+    Codec {
+        name: "fib",
+        encode: FibbonaciEncoder::encode,
+        decode: FibbonaciDecoder::decode,
+        max_number: usize::MAX,
+    },
+    Codec {
+        name: "huffman",
+        encode: HuffmanEncoder::encode,
+        decode: HuffmanDecoder::decode,
+        max_number: usize::MAX,
+    },
+    Codec {
+        name: "rans",
+        encode: RansEncoder::encode,
+        decode: RansDecoder::decode,
+        max_number: usize::MAX,
+    },
+    Codec {
+        name: "simple9",
+        encode: Simple9Encoder::encode,
+        decode: Simple9Decoder::decode,
+        max_number: SIMPLE_MAX_NUMBER,
+    },
+    Codec {
+        name: "simple16",
+        encode: Simple16Encoder::encode,
+        decode: Simple16Decoder::decode,
+        max_number: SIMPLE_MAX_NUMBER,
+    },
+    Codec {
+        name: "pfor",
+        encode: PForDeltaEncoder::encode,
+        decode: PForDeltaDecoder::decode,
+        max_number: usize::MAX,
+    },
+    Codec {
+        name: "auto",
+        encode: AutoEncoder::encode,
+        decode: AutoDecoder::decode,
+        max_number: usize::MAX,
+    },
+];
+
+/// Finds registered codec by its name.
+pub fn find_codec(name: &str) -> Option<&'static Codec> {
+    CODECS.iter().find(|codec| codec.name == name)
+}
+
+/// Trait used for encoding `usize` numbers one by one. Encoder keeps
+/// track of encoded numbers, so every number can use adaptive model
+/// of numbers smaller than its `bound`.
+pub trait AdaptiveNumberEncoder {
+    fn new() -> Self;
+    fn encode_number(&mut self, number: usize, bound: usize);
+    fn finish(self) -> Bits;
+}
+
+/// Trait used for decoding numbers encoded by `AdaptiveNumberEncoder`.
+/// Numbers have to be decoded with the same bounds they were encoded with.
+pub trait AdaptiveNumberDecoder {
+    fn new(bits: Bits) -> Self;
+    fn decode_number(&mut self, bound: usize) -> usize;
 }
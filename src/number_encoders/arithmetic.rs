@@ -0,0 +1,400 @@
+//! Things for implementing adaptive arithmetic encoding and decoding.
+
+use crate::bits::{self, Bit, Bits};
+use crate::number_encoders::{AdaptiveNumberDecoder, AdaptiveNumberEncoder};
+
+const HALF: u64 = 1 << 31;
+const QUARTER: u64 = 1 << 30;
+const STATE_WIDTH: usize = 32;
+
+const MAX_TOTAL: usize = 1 << 24;
+const INCREMENT: usize = 16;
+
+/// Numbers modelled by their own frequency, bigger ones share escape
+/// symbol and follow it coded uniformly. Otherwise every number of a big
+/// bound keeps frequency at least 1, total stays at `MAX_TOTAL` and model
+/// is rescaled on every update.
+const MAX_SYMBOLS: usize = 1 << 16;
+const ESCAPE: usize = MAX_SYMBOLS;
+const ESCAPE_DIGIT_WIDTH: usize = 16;
+
+/// Adaptive frequencies of numbers kept in fenwick tree, so cumulative
+/// frequencies can be found in logarithmic time. Every new number
+/// added by growing bound starts with frequency 1. Numbers from
+/// `MAX_SYMBOLS` on are modelled together by `ESCAPE` symbol.
+struct FrequencyModel {
+    frequencies: Vec<usize>,
+    tree: Vec<usize>,
+    total: usize,
+}
+
+impl FrequencyModel {
+    fn new() -> FrequencyModel {
+        FrequencyModel {
+            frequencies: vec![],
+            tree: vec![0],
+            total: 0,
+        }
+    }
+
+    fn grow(&mut self, bound: usize) {
+        let bound = bound.min(MAX_SYMBOLS + 1);
+
+        while self.frequencies.len() < bound {
+            self.push(1);
+        }
+    }
+
+    fn push(&mut self, frequency: usize) {
+        self.frequencies.push(frequency);
+        self.total += frequency;
+
+        // New node covers itself and previous nodes up to its lowest bit.
+        let index = self.frequencies.len();
+        let children = self.prefix_sum(index - 1) - self.prefix_sum(index - lowest_bit(index));
+
+        self.tree.push(frequency + children);
+    }
+
+    /// Sum of frequencies of numbers smaller than `number`.
+    fn prefix_sum(&self, mut number: usize) -> usize {
+        let mut sum = 0;
+
+        while number > 0 {
+            sum += self.tree[number];
+            number -= lowest_bit(number);
+        }
+
+        sum
+    }
+
+    fn range(&self, number: usize) -> (usize, usize) {
+        let low = self.prefix_sum(number);
+
+        (low, low + self.frequencies[number])
+    }
+
+    /// Finds number which cumulative frequency range contains `target`.
+    fn find(&self, mut target: usize) -> usize {
+        let mut number = 0;
+        let mut step = 1 << (usize::BITS - 1 - self.frequencies.len().leading_zeros());
+
+        while step > 0 {
+            if number + step <= self.frequencies.len() && self.tree[number + step] <= target {
+                number += step;
+                target -= self.tree[number];
+            }
+
+            step >>= 1;
+        }
+
+        number
+    }
+
+    fn update(&mut self, number: usize) {
+        self.frequencies[number] += INCREMENT;
+        self.total += INCREMENT;
+
+        let mut index = number + 1;
+
+        while index < self.tree.len() {
+            self.tree[index] += INCREMENT;
+            index += lowest_bit(index);
+        }
+
+        if self.total > MAX_TOTAL {
+            self.rescale();
+        }
+    }
+
+    fn rescale(&mut self) {
+        let frequencies: Vec<_> = self.frequencies.iter().map(|f| f.div_ceil(2)).collect();
+
+        *self = FrequencyModel::new();
+
+        for frequency in frequencies {
+            self.push(frequency);
+        }
+    }
+}
+
+fn lowest_bit(index: usize) -> usize {
+    index & index.wrapping_neg()
+}
+
+/// Width of escaped numbers for `bound`, they are smaller than `bound - MAX_SYMBOLS`.
+fn escaped_width(bound: usize) -> usize {
+    bits::get_usize_bit_len(bound - MAX_SYMBOLS - 1)
+}
+
+/// Adaptive arithmetic encoder.
+pub struct ArithmeticEncoder {
+    model: FrequencyModel,
+    low: u64,
+    high: u64,
+    pending_bits: usize,
+    bits: Bits,
+}
+
+impl ArithmeticEncoder {
+    /// Narrows interval to [`number_low`, `number_high`) out of `total`.
+    fn encode_range(&mut self, number_low: usize, number_high: usize, total: usize) {
+        let total = total as u64;
+        let range = self.high - self.low + 1;
+
+        self.high = self.low + range * number_high as u64 / total - 1;
+        self.low += range * number_low as u64 / total;
+
+        loop {
+            if self.high < HALF {
+                self.push_bit_with_pending(Bit::ZERO);
+            } else if self.low >= HALF {
+                self.push_bit_with_pending(Bit::ONE);
+                self.low -= HALF;
+                self.high -= HALF;
+            } else if self.low >= QUARTER && self.high < HALF + QUARTER {
+                self.pending_bits += 1;
+                self.low -= QUARTER;
+                self.high -= QUARTER;
+            } else {
+                break;
+            }
+
+            self.low <<= 1;
+            self.high = self.high << 1 | 1;
+        }
+    }
+
+    fn push_bit_with_pending(&mut self, bit: Bit) {
+        let pending_bit = match bit {
+            Bit::ONE => Bit::ZERO,
+            Bit::ZERO => Bit::ONE,
+        };
+
+        self.bits.push_bit(bit);
+
+        for _ in 0..self.pending_bits {
+            match pending_bit {
+                Bit::ONE => self.bits.push_bit(Bit::ONE),
+                Bit::ZERO => self.bits.push_bit(Bit::ZERO),
+            }
+        }
+
+        self.pending_bits = 0;
+    }
+}
+
+impl AdaptiveNumberEncoder for ArithmeticEncoder {
+    fn new() -> Self {
+        ArithmeticEncoder {
+            model: FrequencyModel::new(),
+            low: 0,
+            high: (1 << STATE_WIDTH) - 1,
+            pending_bits: 0,
+            bits: Bits::new(),
+        }
+    }
+
+    fn encode_number(&mut self, number: usize, bound: usize) {
+        self.model.grow(bound);
+
+        let symbol = number.min(ESCAPE);
+        let (symbol_low, symbol_high) = self.model.range(symbol);
+        self.encode_range(symbol_low, symbol_high, self.model.total);
+        self.model.update(symbol);
+
+        if symbol == ESCAPE {
+            let escaped = number - MAX_SYMBOLS;
+            let mut width = escaped_width(bound);
+
+            // Uniform digits, so totals stay small enough for interval precision.
+            while width > 0 {
+                let digit_width = width.min(ESCAPE_DIGIT_WIDTH);
+                width -= digit_width;
+
+                let digit = escaped >> width & ((1 << digit_width) - 1);
+                self.encode_range(digit, digit + 1, 1 << digit_width);
+            }
+        }
+    }
+
+    fn finish(mut self) -> Bits {
+        self.pending_bits += 1;
+
+        if self.low < QUARTER {
+            self.push_bit_with_pending(Bit::ZERO);
+        } else {
+            self.push_bit_with_pending(Bit::ONE);
+        }
+
+        self.bits
+    }
+}
+
+/// Adaptive arithmetic decoder.
+pub struct ArithmeticDecoder {
+    model: FrequencyModel,
+    low: u64,
+    high: u64,
+    value: u64,
+    bits: Bits,
+    index: usize,
+}
+
+impl ArithmeticDecoder {
+    /// Position of encoded number in [0, `total`).
+    fn decode_target(&self, total: usize) -> usize {
+        let range = self.high - self.low + 1;
+
+        (((self.value - self.low + 1) * total as u64 - 1) / range) as usize
+    }
+
+    /// Narrows interval to [`number_low`, `number_high`) out of `total`.
+    fn decode_range(&mut self, number_low: usize, number_high: usize, total: usize) {
+        let total = total as u64;
+        let range = self.high - self.low + 1;
+
+        self.high = self.low + range * number_high as u64 / total - 1;
+        self.low += range * number_low as u64 / total;
+
+        loop {
+            let offset = if self.high < HALF {
+                0
+            } else if self.low >= HALF {
+                HALF
+            } else if self.low >= QUARTER && self.high < HALF + QUARTER {
+                QUARTER
+            } else {
+                break;
+            };
+
+            self.value -= offset;
+            self.low -= offset;
+            self.high -= offset;
+
+            self.low <<= 1;
+            self.high = self.high << 1 | 1;
+            self.value = self.value << 1 | self.next_bit();
+        }
+    }
+
+    // Bits after the end of encoded data are treated as zeros.
+    fn next_bit(&mut self) -> u64 {
+        let bit = match self.index < self.bits.len() {
+            true => self.bits.get_bit(self.index),
+            false => Bit::ZERO,
+        };
+
+        self.index += 1;
+
+        match bit {
+            Bit::ONE => 1,
+            Bit::ZERO => 0,
+        }
+    }
+}
+
+impl AdaptiveNumberDecoder for ArithmeticDecoder {
+    fn new(bits: Bits) -> Self {
+        let mut decoder = ArithmeticDecoder {
+            model: FrequencyModel::new(),
+            low: 0,
+            high: (1 << STATE_WIDTH) - 1,
+            value: 0,
+            bits,
+            index: 0,
+        };
+
+        for _ in 0..STATE_WIDTH {
+            decoder.value = decoder.value << 1 | decoder.next_bit();
+        }
+
+        decoder
+    }
+
+    fn decode_number(&mut self, bound: usize) -> usize {
+        self.model.grow(bound);
+
+        let symbol = self.model.find(self.decode_target(self.model.total));
+        let (symbol_low, symbol_high) = self.model.range(symbol);
+        self.decode_range(symbol_low, symbol_high, self.model.total);
+        self.model.update(symbol);
+
+        if symbol < ESCAPE {
+            return symbol;
+        }
+
+        let mut escaped = 0;
+        let mut width = escaped_width(bound);
+
+        while width > 0 {
+            let digit_width = width.min(ESCAPE_DIGIT_WIDTH);
+            width -= digit_width;
+
+            let digit = self.decode_target(1 << digit_width);
+            self.decode_range(digit, digit + 1, 1 << digit_width);
+
+            escaped = escaped << digit_width | digit;
+        }
+
+        MAX_SYMBOLS + escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frequency_model_works() {
+        let mut model = FrequencyModel::new();
+
+        model.grow(5);
+        model.update(2);
+
+        assert_eq!(5 + INCREMENT, model.total);
+        assert_eq!((2, 3 + INCREMENT), model.range(2));
+        assert_eq!(2, model.find(2 + INCREMENT));
+        assert_eq!(3, model.find(3 + INCREMENT));
+    }
+
+    #[test]
+    fn decode_number_works() {
+        let numbers = [0, 1, 256, 258, 257, 1];
+
+        let mut encoder = ArithmeticEncoder::new();
+
+        for (i, &number) in numbers.iter().enumerate() {
+            encoder.encode_number(number, 256 + i);
+        }
+
+        let mut decoder = ArithmeticDecoder::new(encoder.finish());
+
+        let decoded: Vec<_> = (0..numbers.len())
+            .map(|i| decoder.decode_number(256 + i))
+            .collect();
+
+        assert_eq!(vec![0, 1, 256, 258, 257, 1], decoded);
+    }
+
+    #[test]
+    fn big_bound_works() {
+        let bound = 4 * MAX_SYMBOLS + 7;
+        let numbers: Vec<_> = (0..20_000).map(|i| i * 7919 % bound).collect();
+
+        let mut encoder = ArithmeticEncoder::new();
+
+        for &number in &numbers {
+            encoder.encode_number(number, bound);
+        }
+
+        let mut decoder = ArithmeticDecoder::new(encoder.finish());
+
+        let decoded: Vec<_> = (0..numbers.len())
+            .map(|_| decoder.decode_number(bound))
+            .collect();
+
+        assert_eq!(numbers, decoded);
+        assert_eq!(MAX_SYMBOLS + 1, decoder.model.frequencies.len());
+    }
+}
@@ -0,0 +1,115 @@
+//! Things for choosing the best registered codec for every block of numbers.
+
+use crate::bits::Bits;
+use crate::number_encoders::{NumberDecoder, NumberEncoder, CODECS};
+
+pub const BLOCK_SIZE: usize = 4096;
+
+const TAG_WIDTH: usize = 4;
+const BLOCK_LEN_WIDTH: usize = 32;
+
+// Every registered codec needs its own tag.
+const _: () = assert!(CODECS.len() <= 1 << TAG_WIDTH);
+
+/// Encoder splitting numbers into blocks of `BLOCK_SIZE` and encoding every
+/// block with the codec giving the shortest output among codecs which can
+/// encode its numbers. Every block starts with codec tag and length
+/// of encoded block, so decoder knows how to decode it. Auto codec
+/// registered itself isn't used for blocks.
+pub struct AutoEncoder;
+
+impl NumberEncoder for AutoEncoder {
+    fn encode(numbers: &[usize]) -> Bits {
+        let mut bits = Bits::new();
+
+        for block in numbers.chunks(BLOCK_SIZE) {
+            let max = block.iter().copied().max().unwrap_or(0);
+
+            let (tag, encoded) = CODECS
+                .iter()
+                .enumerate()
+                .filter(|(_, codec)| codec.name != "auto" && max <= codec.max_number)
+                .map(|(tag, codec)| (tag, (codec.encode)(block)))
+                .min_by_key(|(_, encoded)| encoded.len())
+                .expect("there are registered codecs");
+
+            bits.push_number(tag, TAG_WIDTH);
+            bits.push_number(encoded.len(), BLOCK_LEN_WIDTH);
+            bits.append_bits(&encoded);
+        }
+
+        bits
+    }
+}
+
+/// Decoder for blocks encoded by `AutoEncoder`.
+pub struct AutoDecoder;
+
+impl NumberDecoder for AutoDecoder {
+    fn decode(bits: &Bits) -> Vec<usize> {
+        let mut numbers = vec![];
+        let mut index = 0;
+
+        while index < bits.len() {
+            let tag: usize = bits.slice(index, index + TAG_WIDTH).into();
+            index += TAG_WIDTH;
+
+            let block_len: usize = bits.slice(index, index + BLOCK_LEN_WIDTH).into();
+            index += BLOCK_LEN_WIDTH;
+
+            let block = bits.slice(index, index + block_len);
+            index += block_len;
+
+            numbers.append(&mut (CODECS[tag].decode)(&block));
+        }
+
+        numbers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortest_codec_is_chosen() {
+        let numbers = [1; 10];
+
+        let bits = AutoEncoder::encode(&numbers);
+        let tag: usize = bits.slice(0, TAG_WIDTH).into();
+
+        // Omega and gamma both use single bit for 1, the first one wins.
+        assert_eq!("omega", CODECS[tag].name);
+        assert_eq!(TAG_WIDTH + BLOCK_LEN_WIDTH + 10, bits.len());
+    }
+
+    #[test]
+    fn decode_number_works() {
+        let numbers: Vec<_> = (1..3 * BLOCK_SIZE).map(|n| n % 300 + 1).collect();
+
+        let encoded = AutoEncoder::encode(&numbers);
+        let decoded = AutoDecoder::decode(&encoded);
+
+        assert_eq!(numbers, decoded);
+    }
+
+    #[test]
+    fn skewed_block_works() {
+        // Few frequent numbers and many rare ones, which rans rounds up.
+        let mut numbers: Vec<_> = (1..=10).flat_map(|n| [n; 300]).collect();
+        numbers.extend(11..=1000);
+
+        let encoded = AutoEncoder::encode(&numbers);
+
+        assert_eq!(numbers, AutoDecoder::decode(&encoded));
+    }
+
+    #[test]
+    fn large_numbers_work() {
+        let numbers = [1, 1 << 40, usize::MAX];
+
+        let encoded = AutoEncoder::encode(&numbers);
+
+        assert_eq!(vec![1, 1 << 40, usize::MAX], AutoDecoder::decode(&encoded));
+    }
+}
@@ -1,7 +1,7 @@
 //! Things for implementing delta variant of elias encoding and decoding.
 
 use crate::bits::{self, Bit, Bits};
-use crate::number_encoders::{NumberDecoder, NumberEncoder};
+use crate::number_encoders::{CodeLength, NumberDecoder, NumberEncoder};
 
 /// Delta variant of elias encoder.
 pub struct EliasDeltaEncoder;
@@ -53,6 +53,14 @@ impl NumberEncoder for EliasDeltaEncoder {
     }
 }
 
+impl CodeLength for EliasDeltaEncoder {
+    fn code_len(number: usize) -> usize {
+        let number_len = bits::get_usize_bit_len(number);
+
+        number_len + 2 * bits::get_usize_bit_len(number_len) - 2
+    }
+}
+
 /// State machine keeping track of elias delta decoding state.
 enum DecodingState {
     Empty,
@@ -112,11 +120,19 @@ impl EliasDeltaDecoder {
 
 impl NumberDecoder for EliasDeltaDecoder {
     fn decode(bits: &Bits) -> Vec<usize> {
+        EliasDeltaDecoder::decode_prefix(bits, usize::MAX)
+    }
+
+    fn decode_prefix(bits: &Bits, count: usize) -> Vec<usize> {
         let mut numbers = vec![];
 
         let mut decoding_state = DecodingState::Empty;
 
         for bit in bits.iter() {
+            if numbers.len() == count {
+                break;
+            }
+
             decoding_state = match (decoding_state, bit) {
                 (DecodingState::Empty, Bit::ONE) => EliasDeltaDecoder::decode_one(&mut numbers),
                 (DecodingState::Empty, Bit::ZERO) => EliasDeltaDecoder::start_counting_zeros(),
@@ -150,6 +166,15 @@ mod tests {
         assert_eq!([0b00010000, 0b00100100], bits.get_bits());
     }
 
+    #[test]
+    fn code_len_works() {
+        for number in 1..1000 {
+            let bits = EliasDeltaEncoder::encode(&[number]);
+
+            assert_eq!(bits.len(), EliasDeltaEncoder::code_len(number));
+        }
+    }
+
     #[test]
     fn decode_number_works() {
         let numbers = [1, 2, 257, 259, 258, 2];
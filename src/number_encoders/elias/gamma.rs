@@ -1,7 +1,7 @@
 //! Things for implementing gamma variant of elias encoding and decoding.
 
 use crate::bits::{self, Bit, Bits};
-use crate::number_encoders::{NumberDecoder, NumberEncoder};
+use crate::number_encoders::{CodeLength, NumberDecoder, NumberEncoder};
 
 /// Gamma variant of elias encoder.
 pub struct EliasGammaEncoder;
@@ -45,6 +45,12 @@ impl NumberEncoder for EliasGammaEncoder {
     }
 }
 
+impl CodeLength for EliasGammaEncoder {
+    fn code_len(number: usize) -> usize {
+        2 * bits::get_usize_bit_len(number) - 1
+    }
+}
+
 /// State machine keeping track of elias gamma decoding state.
 enum DecodingState {
     Empty,
@@ -92,11 +98,19 @@ impl EliasGammaDecoder {
 
 impl NumberDecoder for EliasGammaDecoder {
     fn decode(bits: &Bits) -> Vec<usize> {
+        EliasGammaDecoder::decode_prefix(bits, usize::MAX)
+    }
+
+    fn decode_prefix(bits: &Bits, count: usize) -> Vec<usize> {
         let mut numbers = vec![];
 
         let mut decoding_state = DecodingState::Empty;
 
         for bit in bits.iter() {
+            if numbers.len() == count {
+                break;
+            }
+
             decoding_state = match (decoding_state, bit) {
                 (DecodingState::Empty, Bit::ONE) => EliasGammaDecoder::decode_one(&mut numbers),
                 (DecodingState::Empty, Bit::ZERO) => EliasGammaDecoder::start_counting_zeros(),
@@ -127,6 +141,15 @@ mod tests {
         assert_eq!([0b00000001, 0b00010010], bits.get_bits());
     }
 
+    #[test]
+    fn code_len_works() {
+        for number in 1..1000 {
+            let bits = EliasGammaEncoder::encode(&[number]);
+
+            assert_eq!(bits.len(), EliasGammaEncoder::code_len(number));
+        }
+    }
+
     #[test]
     fn decode_number_works() {
         let numbers = [1, 2, 257, 259, 258, 2];
@@ -136,4 +159,12 @@ mod tests {
 
         assert_eq!(vec![1, 2, 257, 259, 258, 2], decoded);
     }
+
+    #[test]
+    fn decode_prefix_works() {
+        let encoded = EliasGammaEncoder::encode(&[1, 2, 257, 259, 258, 2]);
+
+        assert_eq!(vec![1, 2, 257], EliasGammaDecoder::decode_prefix(&encoded, 3));
+        assert!(EliasGammaDecoder::decode_prefix(&encoded, 0).is_empty());
+    }
 }
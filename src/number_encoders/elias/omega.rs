@@ -1,7 +1,7 @@
 //! Things for implementing omega variant of elias encoding and decoding.
 
 use crate::bits::{self, Bit, Bits};
-use crate::number_encoders::{NumberDecoder, NumberEncoder};
+use crate::number_encoders::{CodeLength, NumberDecoder, NumberEncoder};
 
 /// Omega variant of elias encoder.
 pub struct EliasOmegaEncoder;
@@ -34,6 +34,19 @@ impl NumberEncoder for EliasOmegaEncoder {
     }
 }
 
+impl CodeLength for EliasOmegaEncoder {
+    fn code_len(mut number: usize) -> usize {
+        let mut len = 1;
+
+        while bits::get_usize_bit_len(number) > 1 {
+            len += bits::get_usize_bit_len(number);
+            number = bits::get_usize_bit_len(number) - 1;
+        }
+
+        len
+    }
+}
+
 /// State machine keeping track of elias omega decoding state.
 enum DecodingState {
     Empty,
@@ -70,11 +83,19 @@ impl EliasOmegaDecoder {
 
 impl NumberDecoder for EliasOmegaDecoder {
     fn decode(bits: &Bits) -> Vec<usize> {
+        EliasOmegaDecoder::decode_prefix(bits, usize::MAX)
+    }
+
+    fn decode_prefix(bits: &Bits, count: usize) -> Vec<usize> {
         let mut numbers = vec![];
 
         let mut decoding_state = DecodingState::Empty;
 
         for bit in bits.iter() {
+            if numbers.len() == count {
+                break;
+            }
+
             decoding_state = match (decoding_state, bit) {
                 (DecodingState::Empty, Bit::ZERO) => EliasOmegaDecoder::decode_one(&mut numbers),
                 (DecodingState::Empty, Bit::ONE) => EliasOmegaDecoder::start_decoding_number(),
@@ -107,6 +128,15 @@ mod tests {
         assert_eq!([0b10111100, 0b01001000], bits.get_bits());
     }
 
+    #[test]
+    fn code_len_works() {
+        for number in 1..1000 {
+            let bits = EliasOmegaEncoder::encode(&[number]);
+
+            assert_eq!(bits.len(), EliasOmegaEncoder::code_len(number));
+        }
+    }
+
     #[test]
     fn decode_number_works() {
         let numbers = [1, 2, 257, 259, 258, 2];
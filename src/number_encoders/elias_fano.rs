@@ -0,0 +1,208 @@
+//! Things for implementing Elias–Fano encoding of sorted numbers.
+
+use crate::bits::rank_select::RankSelect;
+use crate::bits::{self, Bit, Bits};
+use crate::number_encoders::{NumberDecoder, NumberEncoder};
+
+const LEN_WIDTH: usize = 64;
+const LOW_WIDTH_WIDTH: usize = 8;
+
+/// Sorted numbers split into low bits, which are stored as they are,
+/// and high bits, which are stored in unary as gaps between them.
+/// Any number can be accessed without decoding the rest.
+#[derive(Clone, Default)]
+pub struct EliasFano {
+    len: usize,
+    low_width: usize,
+    low_bits: Bits,
+    high_bits: RankSelect,
+}
+
+impl EliasFano {
+    /// Creates new instance of `EliasFano` from `numbers`,
+    /// which have to be sorted in non-decreasing order.
+    pub fn new(numbers: &[usize]) -> EliasFano {
+        assert!(
+            numbers.windows(2).all(|pair| pair[0] <= pair[1]),
+            "numbers have to be sorted"
+        );
+
+        // Low bits width is floor of log2 of average gap between numbers.
+        let low_width = match numbers.last().map(|max| max / numbers.len()) {
+            Some(gap) if gap > 0 => bits::get_usize_bit_len(gap) - 1,
+            _ => 0,
+        };
+
+        let mut low_bits = Bits::new();
+        let mut high_bits = Bits::new();
+
+        for (i, &number) in numbers.iter().enumerate() {
+            low_bits.push_number(number, low_width);
+
+            // Every number sets bit at its high bits plus its index,
+            // so zeros before it count its high bits.
+            while high_bits.len() < (number >> low_width) + i {
+                high_bits.push_bit(Bit::ZERO);
+            }
+
+            high_bits.push_bit(Bit::ONE);
+        }
+
+        EliasFano {
+            len: numbers.len(),
+            low_width,
+            low_bits,
+            high_bits: RankSelect::new(high_bits),
+        }
+    }
+
+    /// Returns number of encoded numbers.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if there are no encoded numbers.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `index`-th number.
+    pub fn get(&self, index: usize) -> Option<usize> {
+        if index >= self.len {
+            return None;
+        }
+
+        let high = self.high_bits.select1(index)? - index;
+
+        Some(high << self.low_width | self.low(index))
+    }
+
+    /// Returns the first number which is greater or equal to `number`.
+    pub fn next_geq(&self, number: usize) -> Option<usize> {
+        let high = number >> self.low_width;
+
+        // Numbers with smaller high bits end with `high`-th zero.
+        let first = match high {
+            0 => 0,
+            _ => self.high_bits.select0(high - 1)? + 1 - high,
+        };
+
+        (first..self.len)
+            .map(|index| self.get(index).unwrap())
+            .find(|&found| found >= number)
+    }
+
+    /// Decodes all numbers.
+    pub fn to_vec(&self) -> Vec<usize> {
+        let mut numbers = Vec::with_capacity(self.len);
+        let mut high = 0;
+
+        for bit in self.high_bits.bits().iter() {
+            match bit {
+                Bit::ZERO => high += 1,
+                Bit::ONE => numbers.push(high << self.low_width | self.low(numbers.len())),
+            }
+        }
+
+        numbers
+    }
+
+    /// Writes numbers count, low bits width and both bit arrays.
+    pub fn to_bits(&self) -> Bits {
+        let mut bits = Bits::new();
+
+        bits.push_number(self.len, LEN_WIDTH);
+        bits.push_number(self.low_width, LOW_WIDTH_WIDTH);
+        bits.append_bits(&self.low_bits);
+        bits.append_bits(self.high_bits.bits());
+
+        bits
+    }
+
+    /// Reads `EliasFano` written by `to_bits`.
+    pub fn from_bits(bits: &Bits) -> EliasFano {
+        let len = bits.get_number(0, LEN_WIDTH);
+        let low_width = bits.get_number(LEN_WIDTH, LOW_WIDTH_WIDTH);
+
+        let low_start = LEN_WIDTH + LOW_WIDTH_WIDTH;
+        let high_start = low_start + len * low_width;
+
+        EliasFano {
+            len,
+            low_width,
+            low_bits: bits.slice(low_start, high_start),
+            high_bits: RankSelect::new(bits.slice(high_start, bits.len())),
+        }
+    }
+
+    fn low(&self, index: usize) -> usize {
+        self.low_bits.get_number(index * self.low_width, self.low_width)
+    }
+}
+
+/// Encodes sorted numbers with Elias–Fano encoding.
+pub struct EliasFanoEncoder;
+
+impl NumberEncoder for EliasFanoEncoder {
+    fn encode(numbers: &[usize]) -> Bits {
+        EliasFano::new(numbers).to_bits()
+    }
+}
+
+/// Decodes numbers encoded by `EliasFanoEncoder`.
+pub struct EliasFanoDecoder;
+
+impl NumberDecoder for EliasFanoDecoder {
+    fn decode(bits: &Bits) -> Vec<usize> {
+        EliasFano::from_bits(bits).to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elias_fano_works() {
+        let numbers = [2, 3, 5, 7, 11, 13, 24];
+
+        let elias_fano = EliasFano::new(&numbers);
+
+        assert_eq!(7, elias_fano.len());
+        assert_eq!(1, elias_fano.low_width);
+        assert_eq!(numbers.to_vec(), elias_fano.to_vec());
+        assert_eq!(Some(11), elias_fano.get(4));
+        assert_eq!(None, elias_fano.get(7));
+    }
+
+    #[test]
+    fn next_geq_works() {
+        let numbers = [0, 0, 4, 9, 100, 101, 1000];
+
+        let elias_fano = EliasFano::new(&numbers);
+
+        assert_eq!(Some(0), elias_fano.next_geq(0));
+        assert_eq!(Some(4), elias_fano.next_geq(1));
+        assert_eq!(Some(9), elias_fano.next_geq(9));
+        assert_eq!(Some(100), elias_fano.next_geq(10));
+        assert_eq!(Some(1000), elias_fano.next_geq(102));
+        assert_eq!(None, elias_fano.next_geq(1001));
+        assert_eq!(None, EliasFano::new(&[]).next_geq(0));
+    }
+
+    #[test]
+    fn decode_number_works() {
+        let numbers: Vec<_> = (0..500).map(|i| i * i / 7).collect();
+
+        let encoded = EliasFanoEncoder::encode(&numbers);
+
+        assert_eq!(numbers, EliasFanoDecoder::decode(&encoded));
+        assert!(EliasFanoDecoder::decode(&EliasFanoEncoder::encode(&[])).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "numbers have to be sorted")]
+    fn unsorted_numbers_panic() {
+        EliasFano::new(&[3, 2]);
+    }
+}
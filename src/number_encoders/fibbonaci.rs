@@ -1,7 +1,37 @@
 //! Things for implementing fibbonaci encoding.
 
 use crate::bits::{Bit, Bits};
-use crate::number_encoders::{NumberDecoder, NumberEncoder};
+use crate::number_encoders::{CodeLength, NumberDecoder, NumberEncoder};
+
+/// Fibbonaci numbers 1, 2, 3, 5, ... up to the biggest one fitting `usize`.
+static FIBBONACI_NUMBERS: [usize; fibbonaci_count()] = fibbonaci_numbers();
+
+const fn fibbonaci_count() -> usize {
+    let (mut previous, mut current) = (1usize, 2usize);
+    let mut count = 2;
+
+    while let Some(next) = previous.checked_add(current) {
+        previous = current;
+        current = next;
+        count += 1;
+    }
+
+    count
+}
+
+const fn fibbonaci_numbers() -> [usize; fibbonaci_count()] {
+    let mut numbers = [1; fibbonaci_count()];
+    numbers[1] = 2;
+
+    let mut i = 2;
+
+    while i < numbers.len() {
+        numbers[i] = numbers[i - 2] + numbers[i - 1];
+        i += 1;
+    }
+
+    numbers
+}
 
 /// Struct holding cached fibbonaci numbers used for fibbonaci encoding.
 pub struct Fibbonaci {
@@ -13,11 +43,13 @@ impl Fibbonaci {
         Fibbonaci { cache: vec![1, 2] }
     }
 
+    /// Finds index of the first fibbonaci number greater than `number`,
+    /// or count of fibbonaci numbers fitting `usize` when there is none.
     pub fn find_greater_index(&mut self, number: usize) -> usize {
         // index and value
         let mut curr_index = 1;
 
-        while self.get(curr_index) <= number {
+        while curr_index < FIBBONACI_NUMBERS.len() && self.get(curr_index) <= number {
             curr_index += 1;
         }
 
@@ -42,6 +74,12 @@ impl Fibbonaci {
     }
 }
 
+impl Default for Fibbonaci {
+    fn default() -> Self {
+        Fibbonaci::new()
+    }
+}
+
 /// Fibbonaci encoder.
 pub struct FibbonaciEncoder;
 
@@ -81,6 +119,13 @@ impl NumberEncoder for FibbonaciEncoder {
     }
 }
 
+impl CodeLength for FibbonaciEncoder {
+    fn code_len(number: usize) -> usize {
+        // Count of fibbonaci numbers not bigger than `number` plus ending bit.
+        FIBBONACI_NUMBERS.partition_point(|&fibbonaci| fibbonaci <= number) + 1
+    }
+}
+
 /// State machine keeping track of fibbonaci decoder decoding state.
 enum DecodingState {
     Empty,
@@ -139,12 +184,20 @@ pub struct FibbonaciDecoder;
 
 impl NumberDecoder for FibbonaciDecoder {
     fn decode(bits: &Bits) -> Vec<usize> {
+        FibbonaciDecoder::decode_prefix(bits, usize::MAX)
+    }
+
+    fn decode_prefix(bits: &Bits, count: usize) -> Vec<usize> {
         let mut numbers = vec![];
         let mut fibbonaci = Fibbonaci::new();
 
         let mut decoding_state = DecodingState::Empty;
 
         for bit in bits.iter() {
+            if numbers.len() == count {
+                break;
+            }
+
             decoding_state = decoding_state.next(bit, &mut numbers, &mut fibbonaci);
         }
 
@@ -174,6 +227,34 @@ mod tests {
         assert_eq!([0b10000101, 0b01100000], bits.get_bits());
     }
 
+    #[test]
+    fn code_len_works() {
+        for number in 1..1000 {
+            let bits = FibbonaciEncoder::encode(&[number]);
+
+            assert_eq!(bits.len(), FibbonaciEncoder::code_len(number));
+        }
+
+        let mut fibbonaci = Fibbonaci::new();
+
+        for number in [1 << 40, 1 << 60, usize::MAX / 2] {
+            assert_eq!(
+                fibbonaci.find_greater_index(number) + 1,
+                FibbonaciEncoder::code_len(number)
+            );
+        }
+
+        assert_eq!(FIBBONACI_NUMBERS.len() + 1, FibbonaciEncoder::code_len(usize::MAX));
+    }
+
+    #[test]
+    fn max_number_works() {
+        let encoded = FibbonaciEncoder::encode(&[usize::MAX, 1]);
+
+        assert_eq!(FibbonaciEncoder::code_len(usize::MAX) + 2, encoded.len());
+        assert_eq!(vec![usize::MAX, 1], FibbonaciDecoder::decode(&encoded));
+    }
+
     #[test]
     fn decode_number_works() {
         let numbers = [1, 2, 257, 259, 258, 2];
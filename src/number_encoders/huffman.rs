@@ -0,0 +1,326 @@
+//! Things for implementing canonical huffman encoding and decoding.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::bits::{Bit, Bits};
+use crate::number_encoders::elias::gamma::{EliasGammaDecoder, EliasGammaEncoder};
+use crate::number_encoders::{NumberDecoder, NumberEncoder};
+use crate::transforms::Transform;
+
+const HEADER_LEN_WIDTH: usize = 64;
+const FIRST_SYMBOL_WIDTH: usize = 64;
+
+/// Longest code, so every code fits in `usize`.
+const MAX_CODE_LEN: usize = 64;
+
+/// Canonical huffman code of symbols that occur in encoded numbers,
+/// stored as (symbol, code length) pairs sorted by code length and symbol.
+struct CanonicalCode {
+    symbols: Vec<(usize, usize)>,
+    codes: Vec<usize>,
+}
+
+impl CanonicalCode {
+    /// Builds optimal code lengths, no longer than `max_len`,
+    /// for symbols with given frequencies.
+    fn from_frequencies(frequencies: &HashMap<usize, usize>, max_len: usize) -> CanonicalCode {
+        // Nodes are leaves (symbols) first and then internal nodes.
+        let mut leaves: Vec<_> = frequencies.keys().copied().collect();
+        leaves.sort_unstable();
+
+        let mut parents: Vec<_> = (0..leaves.len()).collect();
+        let mut heap: BinaryHeap<_> = leaves
+            .iter()
+            .enumerate()
+            .map(|(node, symbol)| Reverse((frequencies[symbol], node)))
+            .collect();
+
+        while heap.len() > 1 {
+            let Reverse((first_frequency, first)) = heap.pop().unwrap();
+            let Reverse((second_frequency, second)) = heap.pop().unwrap();
+
+            let parent = parents.len();
+            parents.push(parent);
+            parents[first] = parent;
+            parents[second] = parent;
+
+            heap.push(Reverse((first_frequency + second_frequency, parent)));
+        }
+
+        let mut symbols: Vec<_> = leaves
+            .iter()
+            .enumerate()
+            .map(|(node, &symbol)| {
+                let mut length = 0;
+                let mut curr_node = node;
+
+                while parents[curr_node] != curr_node {
+                    curr_node = parents[curr_node];
+                    length += 1;
+                }
+
+                // Single symbol still needs one bit to be written.
+                (symbol, length.max(1))
+            })
+            .collect();
+
+        CanonicalCode::limit_lengths(&mut symbols, frequencies, max_len);
+        CanonicalCode::from_lengths(symbols)
+    }
+
+    /// Shortens codes longer than `max_len` and then lengthens the longest
+    /// of the remaining codes until code lengths satisfy Kraft inequality.
+    fn limit_lengths(
+        symbols: &mut [(usize, usize)],
+        frequencies: &HashMap<usize, usize>,
+        max_len: usize,
+    ) {
+        let kraft_limit = 1u128 << max_len;
+        let mut kraft_sum = 0;
+
+        for (_, length) in symbols.iter_mut() {
+            *length = (*length).min(max_len);
+            kraft_sum += 1u128 << (max_len - *length);
+        }
+
+        while kraft_sum > kraft_limit {
+            let (_, length) = symbols
+                .iter_mut()
+                .filter(|(_, length)| *length < max_len)
+                .max_by_key(|(symbol, length)| (*length, Reverse(frequencies[symbol])))
+                .unwrap();
+
+            *length += 1;
+            kraft_sum -= 1u128 << (max_len - *length);
+        }
+    }
+
+    /// Assigns canonical codes to symbols ordered by code length and value.
+    fn from_lengths(mut symbols: Vec<(usize, usize)>) -> CanonicalCode {
+        symbols.sort_unstable_by_key(|&(symbol, length)| (length, symbol));
+
+        let mut codes = Vec::with_capacity(symbols.len());
+        // Code after the last one can need `MAX_CODE_LEN + 1` bits.
+        let mut code = 0u128;
+        let mut prev_length = 0;
+
+        for &(_, length) in &symbols {
+            code <<= length - prev_length;
+            codes.push(code as usize);
+
+            code += 1;
+            prev_length = length;
+        }
+
+        CanonicalCode { symbols, codes }
+    }
+
+    /// Writes the first of symbols ordered by value as is, and then gamma
+    /// encoded number of symbols, differences of consecutive symbols
+    /// and zigzagged differences of their code lengths.
+    fn encode_lengths(&self) -> Bits {
+        let mut symbols = self.symbols.clone();
+        symbols.sort_unstable();
+
+        let mut lengths: Vec<_> = symbols.iter().map(|&(_, length)| length).collect();
+        Transform::Delta.apply(&mut lengths);
+        Transform::ZigZag.apply(&mut lengths);
+
+        let mut numbers = vec![symbols.len() + 1];
+        numbers.extend(symbols.windows(2).map(|pair| pair[1].0 - pair[0].0));
+        numbers.extend(lengths.iter().map(|length| length + 1));
+
+        let first_symbol = symbols.first().map_or(0, |&(symbol, _)| symbol);
+
+        let mut bits = Bits::new();
+        bits.push_number(first_symbol, FIRST_SYMBOL_WIDTH);
+        bits.append_bits(&EliasGammaEncoder::encode(&numbers));
+        bits
+    }
+
+    fn decode_lengths(bits: &Bits) -> CanonicalCode {
+        let first_symbol = bits.get_number(0, FIRST_SYMBOL_WIDTH);
+        let numbers = EliasGammaDecoder::decode(&bits.slice(FIRST_SYMBOL_WIDTH, bits.len()));
+
+        let count = numbers[0] - 1;
+        let gaps = &numbers[1..count.max(1)];
+        let mut lengths: Vec<_> = numbers[count.max(1)..].iter().map(|length| length - 1).collect();
+        Transform::ZigZag.invert(&mut lengths, 0);
+        Transform::Delta.invert(&mut lengths, 0);
+
+        let mut symbol = first_symbol;
+        let symbols = std::iter::once(0)
+            .chain(gaps.iter().copied())
+            .zip(lengths)
+            .map(|(gap, length)| {
+                symbol += gap;
+                (symbol, length)
+            })
+            .collect();
+
+        CanonicalCode::from_lengths(symbols)
+    }
+}
+
+/// Canonical huffman encoder. Table of occurring symbols and their code
+/// lengths is written in the header, so the same code can be rebuilt by decoder.
+pub struct HuffmanEncoder;
+
+impl NumberEncoder for HuffmanEncoder {
+    fn encode(numbers: &[usize]) -> Bits {
+        let mut frequencies = HashMap::new();
+
+        for &number in numbers {
+            *frequencies.entry(number).or_insert(0) += 1;
+        }
+
+        let code = CanonicalCode::from_frequencies(&frequencies, MAX_CODE_LEN);
+        let header = code.encode_lengths();
+
+        let codes: HashMap<_, _> = code
+            .symbols
+            .iter()
+            .zip(&code.codes)
+            .map(|(&(symbol, length), &code)| (symbol, (code, length)))
+            .collect();
+
+        let mut bits = Bits::new();
+        bits.push_number(header.len(), HEADER_LEN_WIDTH);
+        bits.append_bits(&header);
+
+        for number in numbers {
+            let (code, length) = codes[number];
+            bits.push_number(code, length);
+        }
+
+        bits
+    }
+}
+
+/// Canonical huffman decoder.
+pub struct HuffmanDecoder;
+
+impl NumberDecoder for HuffmanDecoder {
+    fn decode(bits: &Bits) -> Vec<usize> {
+        HuffmanDecoder::decode_prefix(bits, usize::MAX)
+    }
+
+    fn decode_prefix(bits: &Bits, count: usize) -> Vec<usize> {
+        let header_len: usize = bits.slice(0, HEADER_LEN_WIDTH).into();
+        let header_end = HEADER_LEN_WIDTH + header_len;
+
+        let code = CanonicalCode::decode_lengths(&bits.slice(HEADER_LEN_WIDTH, header_end));
+
+        // For every code length: first code, its index in sorted symbols
+        // and number of codes with that length.
+        let mut first_codes: HashMap<usize, (usize, usize, usize)> = HashMap::new();
+
+        for (index, &(_, length)) in code.symbols.iter().enumerate() {
+            first_codes
+                .entry(length)
+                .or_insert((code.codes[index], index, 0))
+                .2 += 1;
+        }
+
+        let mut numbers = vec![];
+        let mut curr_code = 0;
+        let mut curr_len = 0;
+
+        for bit in (header_end..bits.len()).map(|index| bits.get_bit(index)) {
+            if numbers.len() == count {
+                break;
+            }
+
+            curr_code <<= 1;
+            curr_len += 1;
+
+            if let Bit::ONE = bit {
+                curr_code |= 1;
+            }
+
+            if let Some(&(first_code, index, count)) = first_codes.get(&curr_len) {
+                if curr_code >= first_code && curr_code - first_code < count {
+                    numbers.push(code.symbols[index + curr_code - first_code].0);
+                    curr_code = 0;
+                    curr_len = 0;
+                }
+            }
+        }
+
+        numbers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_code_works() {
+        let frequencies = [(1, 5), (2, 2), (3, 1), (4, 1)].into_iter().collect();
+
+        let code = CanonicalCode::from_frequencies(&frequencies, MAX_CODE_LEN);
+
+        assert_eq!(vec![(1, 1), (2, 2), (3, 3), (4, 3)], code.symbols);
+        assert_eq!(vec![0b0, 0b10, 0b110, 0b111], code.codes);
+    }
+
+    #[test]
+    fn limit_lengths_works() {
+        let frequencies = [(1, 1), (2, 1), (3, 2), (4, 3), (5, 5), (6, 8), (7, 13)]
+            .into_iter()
+            .collect();
+
+        let code = CanonicalCode::from_frequencies(&frequencies, 4);
+
+        let kraft_sum: usize = code.symbols.iter().map(|&(_, length)| 1 << (4 - length)).sum();
+        assert!(code.symbols.iter().all(|&(_, length)| length <= 4));
+        assert!(kraft_sum <= 1 << 4);
+    }
+
+    #[test]
+    fn decode_number_works() {
+        let numbers = [1, 2, 257, 259, 258, 2];
+
+        let encoded = HuffmanEncoder::encode(&numbers);
+        let decoded = HuffmanDecoder::decode(&encoded);
+
+        assert_eq!(vec![1, 2, 257, 259, 258, 2], decoded);
+    }
+
+    #[test]
+    fn decode_prefix_works() {
+        let encoded = HuffmanEncoder::encode(&[1, 2, 257, 259, 258, 2]);
+
+        assert_eq!(vec![1, 2, 257], HuffmanDecoder::decode_prefix(&encoded, 3));
+    }
+
+    #[test]
+    fn single_symbol_works() {
+        let numbers = [7, 7, 7];
+
+        let encoded = HuffmanEncoder::encode(&numbers);
+        let decoded = HuffmanDecoder::decode(&encoded);
+
+        assert_eq!(vec![7, 7, 7], decoded);
+    }
+
+    #[test]
+    fn large_symbols_work() {
+        let numbers = [1, 1 << 40, usize::MAX, 1];
+
+        let encoded = HuffmanEncoder::encode(&numbers);
+        let decoded = HuffmanDecoder::decode(&encoded);
+
+        assert!(encoded.len() < 400);
+        assert_eq!(vec![1, 1 << 40, usize::MAX, 1], decoded);
+    }
+
+    #[test]
+    fn empty_works() {
+        let encoded = HuffmanEncoder::encode(&[]);
+
+        assert!(HuffmanDecoder::decode(&encoded).is_empty());
+    }
+}
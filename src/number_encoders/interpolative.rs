@@ -0,0 +1,169 @@
+//! Things for implementing binary interpolative encoding of strictly increasing numbers.
+
+use crate::bits::Bits;
+use crate::number_encoders::truncated_binary;
+use crate::number_encoders::{NumberDecoder, NumberEncoder};
+
+const HEADER_WIDTH: usize = 64;
+
+/// Binary interpolative (Moffat–Stuiver) encoder. Middle number is encoded
+/// in range left for it by its bounds and numbers around it, then both halves
+/// are encoded the same way with narrowed bounds. Dense runs take no bits.
+pub struct InterpolativeEncoder;
+
+impl InterpolativeEncoder {
+    fn encode_range(numbers: &[usize], low: usize, high: usize, bits: &mut Bits) {
+        if numbers.is_empty() {
+            return;
+        }
+
+        let middle = numbers.len() / 2;
+        let number = numbers[middle];
+
+        // Numbers before and after middle one need distinct values too.
+        let number_low = low + middle;
+        let number_high = high - (numbers.len() - middle - 1);
+
+        let range = number_high - number_low + 1;
+        truncated_binary::encode_number(number - number_low, range, bits);
+
+        if middle > 0 {
+            InterpolativeEncoder::encode_range(&numbers[..middle], low, number - 1, bits);
+        }
+
+        InterpolativeEncoder::encode_range(&numbers[middle + 1..], number + 1, high, bits);
+    }
+}
+
+impl NumberEncoder for InterpolativeEncoder {
+    fn encode(numbers: &[usize]) -> Bits {
+        assert!(
+            numbers.windows(2).all(|pair| pair[0] < pair[1]),
+            "numbers have to be strictly increasing"
+        );
+
+        let mut bits = Bits::new();
+        bits.push_number(numbers.len(), HEADER_WIDTH);
+
+        // The last number bounds all the others.
+        if let Some((&max, rest)) = numbers.split_last() {
+            bits.push_number(max, HEADER_WIDTH);
+
+            if !rest.is_empty() {
+                InterpolativeEncoder::encode_range(rest, 0, max - 1, &mut bits);
+            }
+        }
+
+        bits
+    }
+}
+
+/// Binary interpolative (Moffat–Stuiver) decoder.
+pub struct InterpolativeDecoder;
+
+impl InterpolativeDecoder {
+    fn decode_range(
+        len: usize,
+        low: usize,
+        high: usize,
+        bits: &Bits,
+        position: &mut usize,
+        numbers: &mut Vec<usize>,
+    ) {
+        if len == 0 {
+            return;
+        }
+
+        let middle = len / 2;
+
+        let number_low = low + middle;
+        let number_high = high - (len - middle - 1);
+
+        let range = number_high - number_low + 1;
+        let number = number_low + truncated_binary::decode_number(range, bits, position);
+
+        if middle > 0 {
+            let high = number - 1;
+            InterpolativeDecoder::decode_range(middle, low, high, bits, position, numbers);
+        }
+
+        numbers.push(number);
+
+        let len = len - middle - 1;
+        InterpolativeDecoder::decode_range(len, number + 1, high, bits, position, numbers);
+    }
+}
+
+impl NumberDecoder for InterpolativeDecoder {
+    fn decode(bits: &Bits) -> Vec<usize> {
+        let len = bits.get_number(0, HEADER_WIDTH);
+
+        if len == 0 {
+            return vec![];
+        }
+
+        let max = bits.get_number(HEADER_WIDTH, HEADER_WIDTH);
+
+        let mut numbers = Vec::with_capacity(len);
+        let mut position = 2 * HEADER_WIDTH;
+
+        if len > 1 {
+            InterpolativeDecoder::decode_range(
+                len - 1,
+                0,
+                max - 1,
+                bits,
+                &mut position,
+                &mut numbers,
+            );
+        }
+
+        numbers.push(max);
+
+        numbers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EliasGammaEncoder;
+
+    #[test]
+    fn decode_number_works() {
+        let numbers = [3, 8, 9, 11, 12, 13, 17];
+
+        let encoded = InterpolativeEncoder::encode(&numbers);
+
+        assert_eq!(numbers.to_vec(), InterpolativeDecoder::decode(&encoded));
+        assert_eq!(vec![0], InterpolativeDecoder::decode(&InterpolativeEncoder::encode(&[0])));
+        assert!(InterpolativeDecoder::decode(&InterpolativeEncoder::encode(&[])).is_empty());
+    }
+
+    #[test]
+    fn dense_numbers_take_no_bits() {
+        let numbers: Vec<_> = (0..1000).collect();
+
+        assert_eq!(2 * HEADER_WIDTH, InterpolativeEncoder::encode(&numbers).len());
+    }
+
+    #[test]
+    fn beats_gamma_gaps_works() {
+        let numbers: Vec<_> = (0..2000).filter(|i| i % 3 != 0 || i % 7 == 0).collect();
+        let gaps: Vec<_> = numbers
+            .iter()
+            .scan(0, |last, &number| Some(number + 1 - std::mem::replace(last, number + 1)))
+            .collect();
+
+        let encoded = InterpolativeEncoder::encode(&numbers);
+
+        assert_eq!(numbers, InterpolativeDecoder::decode(&encoded));
+        assert!(encoded.len() < EliasGammaEncoder::encode(&gaps).len());
+    }
+
+    #[test]
+    #[should_panic(expected = "numbers have to be strictly increasing")]
+    fn repeated_numbers_panic() {
+        InterpolativeEncoder::encode(&[1, 1]);
+    }
+}
@@ -0,0 +1,197 @@
+//! Things for implementing PForDelta (patched frame of reference) encoding.
+
+use crate::bits::{self, Bits};
+use crate::number_encoders::{NumberDecoder, NumberEncoder};
+
+pub const BLOCK_SIZE: usize = 128;
+
+const COUNT_BYTES: usize = 8;
+
+/// PForDelta encoder. Every block of `BLOCK_SIZE` numbers is packed with
+/// width chosen to give the shortest block. Numbers which don't fit it are
+/// exceptions, their positions and high bits are written after the block.
+/// Numbers are packed as they are, so sorted numbers should be turned into gaps first.
+pub struct PForDeltaEncoder;
+
+impl PForDeltaEncoder {
+    fn encode_block(block: &[usize], bytes: &mut Vec<u8>) {
+        let width = (0..=usize::BITS)
+            .min_by_key(|&width| block_len(block, width))
+            .expect("there are widths");
+
+        let exceptions: Vec<_> = block
+            .iter()
+            .enumerate()
+            .filter(|(_, &number)| high_bits(number, width) > 0)
+            .map(|(position, &number)| (position as u8, high_bits(number, width)))
+            .collect();
+
+        let exceptions_width = exceptions_width(exceptions.iter().map(|&(_, high)| high));
+
+        bytes.extend_from_slice(&[width as u8, exceptions.len() as u8, exceptions_width as u8]);
+        pack(block.iter().copied(), width, bytes);
+        bytes.extend(exceptions.iter().map(|&(position, _)| position));
+        pack(exceptions.iter().map(|&(_, high)| high), exceptions_width, bytes);
+    }
+}
+
+impl NumberEncoder for PForDeltaEncoder {
+    fn encode(numbers: &[usize]) -> Bits {
+        let mut bytes = (numbers.len() as u64).to_be_bytes().to_vec();
+
+        for block in numbers.chunks(BLOCK_SIZE) {
+            PForDeltaEncoder::encode_block(block, &mut bytes);
+        }
+
+        Bits::from_vec(bytes.len() * 8, bytes)
+    }
+}
+
+/// PForDelta decoder.
+pub struct PForDeltaDecoder;
+
+impl NumberDecoder for PForDeltaDecoder {
+    fn decode(bits: &Bits) -> Vec<usize> {
+        let bytes = bits.get_bits();
+
+        let count = u64::from_be_bytes(bytes[..COUNT_BYTES].try_into().unwrap()) as usize;
+        let mut position = COUNT_BYTES;
+
+        let mut numbers = Vec::with_capacity(count);
+
+        while numbers.len() < count {
+            let block_len = (count - numbers.len()).min(BLOCK_SIZE);
+
+            let width = bytes[position] as u32;
+            let exceptions_count = bytes[position + 1] as usize;
+            let exceptions_width = bytes[position + 2] as u32;
+            position += 3;
+
+            let mut block = unpack(bytes, &mut position, block_len, width);
+
+            let positions = &bytes[position..position + exceptions_count];
+            position += exceptions_count;
+
+            let highs = unpack(bytes, &mut position, exceptions_count, exceptions_width);
+
+            for (&exception, high) in positions.iter().zip(highs) {
+                block[exception as usize] |= high << width;
+            }
+
+            numbers.append(&mut block);
+        }
+
+        numbers
+    }
+}
+
+/// Length in bits of `block` packed with `width`, exceptions included.
+fn block_len(block: &[usize], width: u32) -> usize {
+    let highs = block
+        .iter()
+        .map(|&number| high_bits(number, width))
+        .filter(|&high| high > 0);
+
+    let exceptions_count = highs.clone().count();
+
+    block.len() * width as usize + exceptions_count * (8 + exceptions_width(highs) as usize)
+}
+
+fn exceptions_width<I>(highs: I) -> u32
+where
+    I: Iterator<Item = usize>,
+{
+    highs.max().map_or(0, |max| bits::get_usize_bit_len(max) as u32)
+}
+
+fn high_bits(number: usize, width: u32) -> usize {
+    number.checked_shr(width).unwrap_or(0)
+}
+
+fn low_mask(width: u32) -> usize {
+    usize::MAX.checked_shr(usize::BITS - width).unwrap_or(0)
+}
+
+/// Packs `width` low bits of every number, padding the last byte with zeros.
+fn pack<I>(numbers: I, width: u32, bytes: &mut Vec<u8>)
+where
+    I: Iterator<Item = usize>,
+{
+    let mut buffer: u128 = 0;
+    let mut buffer_len = 0;
+
+    for number in numbers {
+        buffer = buffer << width | (number & low_mask(width)) as u128;
+        buffer_len += width;
+
+        while buffer_len >= 8 {
+            buffer_len -= 8;
+            bytes.push((buffer >> buffer_len) as u8);
+        }
+    }
+
+    if buffer_len > 0 {
+        bytes.push((buffer << (8 - buffer_len)) as u8);
+    }
+}
+
+/// Unpacks `count` numbers packed by `pack` starting at `position`,
+/// which is moved after their last byte.
+fn unpack(bytes: &[u8], position: &mut usize, count: usize, width: u32) -> Vec<usize> {
+    let mut buffer: u128 = 0;
+    let mut buffer_len = 0;
+
+    (0..count)
+        .map(|_| {
+            while buffer_len < width {
+                buffer = buffer << 8 | bytes[*position] as u128;
+                *position += 1;
+                buffer_len += 8;
+            }
+
+            buffer_len -= width;
+
+            (buffer >> buffer_len) as usize & low_mask(width)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_works() {
+        let mut bytes = vec![];
+        pack([1, 2, 3, 4].into_iter(), 3, &mut bytes);
+
+        assert_eq!(vec![0b00101001, 0b11000000], bytes);
+        assert_eq!(vec![1, 2, 3, 4], unpack(&bytes, &mut 0, 4, 3));
+    }
+
+    #[test]
+    fn exceptions_works() {
+        let mut numbers = vec![5; BLOCK_SIZE];
+        numbers[10] = 1_000_000;
+        numbers[77] = usize::MAX;
+
+        let bits = PForDeltaEncoder::encode(&numbers);
+
+        // Count, block header, 3 bit numbers, exception positions and high bits.
+        assert_eq!(8 + 3 + 48 + 2 + 16, bits.get_bits().len());
+        assert_eq!(numbers, PForDeltaDecoder::decode(&bits));
+    }
+
+    #[test]
+    fn decode_number_works() {
+        let numbers: Vec<_> = (0..1000)
+            .map(|n| n * n % 700 + (((n % 97 == 0) as usize) << 40))
+            .collect();
+
+        let bits = PForDeltaEncoder::encode(&numbers);
+
+        assert_eq!(numbers, PForDeltaDecoder::decode(&bits));
+        assert!(PForDeltaDecoder::decode(&PForDeltaEncoder::encode(&[])).is_empty());
+        assert_eq!(vec![0, 0], PForDeltaDecoder::decode(&PForDeltaEncoder::encode(&[0, 0])));
+    }
+}
@@ -0,0 +1,240 @@
+//! Things for implementing adaptive binary range encoding and decoding.
+
+use crate::bits::{self, Bits};
+use crate::number_encoders::{AdaptiveNumberDecoder, AdaptiveNumberEncoder};
+
+const TOP: u32 = 1 << 24;
+const PROBABILITY_WIDTH: u32 = 11;
+const PROBABILITY_ONE: u16 = 1 << PROBABILITY_WIDTH;
+const ADAPTATION_SHIFT: u32 = 5;
+
+/// Probabilities of zero bit for every node of binary tree of numbers.
+/// Every bit width of numbers has its own tree.
+struct BitTreeModel {
+    probabilities: Vec<Vec<u16>>,
+}
+
+impl BitTreeModel {
+    fn new() -> BitTreeModel {
+        BitTreeModel {
+            probabilities: vec![],
+        }
+    }
+
+    fn tree(&mut self, width: usize) -> &mut Vec<u16> {
+        while self.probabilities.len() <= width {
+            let tree_size = 1 << self.probabilities.len();
+            self.probabilities
+                .push(vec![PROBABILITY_ONE / 2; tree_size]);
+        }
+
+        &mut self.probabilities[width]
+    }
+}
+
+/// Number of bits needed to write every number smaller than `bound`.
+fn bound_width(bound: usize) -> usize {
+    bits::get_usize_bit_len(bound.max(2) - 1)
+}
+
+fn adapt(probability: &mut u16, bit: bool) {
+    if bit {
+        *probability -= *probability >> ADAPTATION_SHIFT;
+    } else {
+        *probability += (PROBABILITY_ONE - *probability) >> ADAPTATION_SHIFT;
+    }
+}
+
+/// Adaptive binary range encoder. Numbers are written bit by bit
+/// from the most significant one, every bit with its own probability.
+pub struct RangeEncoder {
+    model: BitTreeModel,
+    low: u64,
+    range: u32,
+    cache: u8,
+    cache_size: usize,
+    bits: Bits,
+}
+
+impl RangeEncoder {
+    fn encode_bit(&mut self, probability: &mut u16, bit: bool) {
+        let bound = (self.range >> PROBABILITY_WIDTH) * *probability as u32;
+
+        if bit {
+            self.low += bound as u64;
+            self.range -= bound;
+        } else {
+            self.range = bound;
+        }
+
+        adapt(probability, bit);
+
+        while self.range < TOP {
+            self.range <<= 8;
+            self.shift_low();
+        }
+    }
+
+    // Writes top byte of low, delaying 0xFF bytes until carry is known.
+    fn shift_low(&mut self) {
+        if self.low < 0xFF000000 || self.low > 0xFFFFFFFF {
+            let carry = (self.low >> 32) as u8;
+            let mut byte = self.cache;
+
+            for _ in 0..self.cache_size {
+                self.bits.push_number(byte.wrapping_add(carry) as usize, 8);
+                byte = 0xFF;
+            }
+
+            self.cache_size = 0;
+            self.cache = (self.low >> 24) as u8;
+        }
+
+        self.cache_size += 1;
+        self.low = (self.low & 0x00FFFFFF) << 8;
+    }
+}
+
+impl AdaptiveNumberEncoder for RangeEncoder {
+    fn new() -> Self {
+        RangeEncoder {
+            model: BitTreeModel::new(),
+            low: 0,
+            range: u32::MAX,
+            cache: 0,
+            cache_size: 1,
+            bits: Bits::new(),
+        }
+    }
+
+    fn encode_number(&mut self, number: usize, bound: usize) {
+        let width = bound_width(bound);
+        let mut tree = std::mem::take(self.model.tree(width));
+        let mut node = 1;
+
+        for i in (0..width).rev() {
+            let bit = number & 1 << i > 0;
+
+            self.encode_bit(&mut tree[node], bit);
+            node = node << 1 | bit as usize;
+        }
+
+        self.model.probabilities[width] = tree;
+    }
+
+    fn finish(mut self) -> Bits {
+        for _ in 0..5 {
+            self.shift_low();
+        }
+
+        self.bits
+    }
+}
+
+/// Adaptive binary range decoder.
+pub struct RangeDecoder {
+    model: BitTreeModel,
+    code: u32,
+    range: u32,
+    bits: Bits,
+    index: usize,
+}
+
+impl RangeDecoder {
+    // Bytes after the end of encoded data are treated as zeros.
+    fn next_byte(&mut self) -> u32 {
+        let bytes = self.bits.get_bits();
+        let byte = bytes.get(self.index).copied().unwrap_or(0);
+
+        self.index += 1;
+
+        byte as u32
+    }
+
+    fn decode_bit(&mut self, probability: &mut u16) -> bool {
+        let bound = (self.range >> PROBABILITY_WIDTH) * *probability as u32;
+
+        let bit = if self.code < bound {
+            self.range = bound;
+            false
+        } else {
+            self.code -= bound;
+            self.range -= bound;
+            true
+        };
+
+        adapt(probability, bit);
+
+        while self.range < TOP {
+            self.range <<= 8;
+            self.code = self.code << 8 | self.next_byte();
+        }
+
+        bit
+    }
+}
+
+impl AdaptiveNumberDecoder for RangeDecoder {
+    fn new(bits: Bits) -> Self {
+        let mut decoder = RangeDecoder {
+            model: BitTreeModel::new(),
+            code: 0,
+            range: u32::MAX,
+            bits,
+            index: 0,
+        };
+
+        // First byte is always zero cache byte written by encoder.
+        for _ in 0..5 {
+            decoder.code = decoder.code << 8 | decoder.next_byte();
+        }
+
+        decoder
+    }
+
+    fn decode_number(&mut self, bound: usize) -> usize {
+        let width = bound_width(bound);
+        let mut tree = std::mem::take(self.model.tree(width));
+        let mut node = 1;
+
+        for _ in 0..width {
+            let bit = self.decode_bit(&mut tree[node]);
+            node = node << 1 | bit as usize;
+        }
+
+        self.model.probabilities[width] = tree;
+
+        node - (1 << width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bound_width_works() {
+        assert_eq!(1, bound_width(1));
+        assert_eq!(8, bound_width(256));
+        assert_eq!(9, bound_width(257));
+    }
+
+    #[test]
+    fn decode_number_works() {
+        let numbers = [0, 1, 256, 258, 257, 1];
+
+        let mut encoder = RangeEncoder::new();
+
+        for (i, &number) in numbers.iter().enumerate() {
+            encoder.encode_number(number, 256 + i);
+        }
+
+        let mut decoder = RangeDecoder::new(encoder.finish());
+
+        let decoded: Vec<_> = (0..numbers.len())
+            .map(|i| decoder.decode_number(256 + i))
+            .collect();
+
+        assert_eq!(vec![0, 1, 256, 258, 257, 1], decoded);
+    }
+}
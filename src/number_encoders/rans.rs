@@ -0,0 +1,304 @@
+//! Things for implementing static rANS encoding and decoding.
+
+use std::collections::HashMap;
+
+use crate::bits::{self, Bits};
+use crate::number_encoders::elias::gamma::{EliasGammaDecoder, EliasGammaEncoder};
+use crate::number_encoders::{NumberDecoder, NumberEncoder};
+
+const HEADER_LEN_WIDTH: usize = 64;
+const FIRST_SYMBOL_WIDTH: usize = 64;
+const STATE_WIDTH: usize = 64;
+const WORD_WIDTH: usize = 32;
+
+// Lower bound of normalized state, state is kept in `[STATE_LOW, STATE_LOW << 32)`.
+const STATE_LOW: u64 = 1 << 31;
+
+const MIN_SCALE_BITS: usize = 12;
+const MAX_SCALE_BITS: usize = 31;
+
+/// Frequencies of occurring numbers, sorted by value, quantized
+/// so they sum up to `1 << scale_bits`.
+struct FrequencyTable {
+    scale_bits: usize,
+    symbols: Vec<usize>,
+    frequencies: Vec<u64>,
+    starts: Vec<u64>,
+}
+
+impl FrequencyTable {
+    fn from_numbers(numbers: &[usize]) -> FrequencyTable {
+        let mut counts = HashMap::new();
+
+        for &number in numbers {
+            *counts.entry(number).or_insert(0) += 1;
+        }
+
+        // Few spare bits so every used number gets frequency of at least one
+        // and rare numbers rounded up don't take much from frequent ones.
+        let scale_bits =
+            (bits::get_usize_bit_len(counts.len()) + 3).clamp(MIN_SCALE_BITS, MAX_SCALE_BITS);
+        let scale: u64 = 1 << scale_bits;
+
+        assert!(counts.len() as u64 <= scale, "too many different numbers for rans");
+
+        let mut symbols: Vec<_> = counts.keys().copied().collect();
+        symbols.sort_unstable();
+
+        let mut frequencies: Vec<_> = symbols
+            .iter()
+            .map(|number| (counts[number] * scale / numbers.len() as u64).max(1))
+            .collect();
+
+        FrequencyTable::normalize(&mut frequencies, scale);
+
+        FrequencyTable::from_frequencies(scale_bits, symbols, frequencies)
+    }
+
+    /// Fixes rounding errors, so `frequencies` sum up to `scale`. Missing
+    /// part goes to the most frequent number, excess is taken from the
+    /// biggest frequencies proportionally, keeping every frequency positive.
+    fn normalize(frequencies: &mut [u64], scale: u64) {
+        let sum: u64 = frequencies.iter().sum();
+
+        let mut by_frequency: Vec<_> = (0..frequencies.len()).collect();
+        by_frequency.sort_by_key(|&n| std::cmp::Reverse(frequencies[n]));
+
+        if sum <= scale {
+            if let Some(&most_frequent) = by_frequency.first() {
+                frequencies[most_frequent] += scale - sum;
+            }
+
+            return;
+        }
+
+        let excess = sum - scale;
+        let reducible: u64 = frequencies.iter().filter(|&&f| f > 0).map(|f| f - 1).sum();
+        let mut remaining = excess;
+
+        for number in by_frequency {
+            let frequency = frequencies[number];
+
+            if remaining == 0 || frequency <= 1 {
+                break;
+            }
+
+            let taken = (excess * (frequency - 1))
+                .div_ceil(reducible)
+                .min(frequency - 1)
+                .min(remaining);
+
+            frequencies[number] -= taken;
+            remaining -= taken;
+        }
+    }
+
+    fn from_frequencies(
+        scale_bits: usize,
+        symbols: Vec<usize>,
+        frequencies: Vec<u64>,
+    ) -> FrequencyTable {
+        let starts = frequencies
+            .iter()
+            .scan(0, |start, &frequency| {
+                *start += frequency;
+                Some(*start - frequency)
+            })
+            .collect();
+
+        FrequencyTable {
+            scale_bits,
+            symbols,
+            frequencies,
+            starts,
+        }
+    }
+
+    /// Finds index of number which frequency range contains `slot`.
+    fn find(&self, slot: u64) -> usize {
+        self.starts.partition_point(|&start| start <= slot) - 1
+    }
+
+    /// Writes the smallest number as is, and then numbers count, scale,
+    /// differences of consecutive numbers and their frequencies
+    /// as gamma encoded numbers.
+    fn encode_header(&self, numbers_count: usize) -> Bits {
+        let mut numbers = vec![numbers_count + 1, self.scale_bits, self.symbols.len() + 1];
+        numbers.extend(self.symbols.windows(2).map(|pair| pair[1] - pair[0]));
+        numbers.extend(self.frequencies.iter().map(|&f| f as usize));
+
+        let first_symbol = self.symbols.first().copied().unwrap_or(0);
+
+        let mut bits = Bits::new();
+        bits.push_number(first_symbol, FIRST_SYMBOL_WIDTH);
+        bits.append_bits(&EliasGammaEncoder::encode(&numbers));
+        bits
+    }
+
+    fn decode_header(bits: &Bits) -> (usize, FrequencyTable) {
+        let first_symbol = bits.get_number(0, FIRST_SYMBOL_WIDTH);
+        let numbers = EliasGammaDecoder::decode(&bits.slice(FIRST_SYMBOL_WIDTH, bits.len()));
+
+        let gaps_end = 3 + (numbers[2] - 1).saturating_sub(1);
+        let symbols = std::iter::once(first_symbol)
+            .chain(numbers[3..gaps_end].iter().copied())
+            .scan(0, |symbol, gap| {
+                *symbol += gap;
+                Some(*symbol)
+            })
+            .take(numbers[2] - 1)
+            .collect();
+        let frequencies = numbers[gaps_end..].iter().map(|&f| f as u64).collect();
+
+        (
+            numbers[0] - 1,
+            FrequencyTable::from_frequencies(numbers[1], symbols, frequencies),
+        )
+    }
+}
+
+/// Static rANS encoder. Quantized frequencies of occurring numbers are
+/// written in the header, so decoder can use the same frequencies.
+pub struct RansEncoder;
+
+impl NumberEncoder for RansEncoder {
+    fn encode(numbers: &[usize]) -> Bits {
+        let table = FrequencyTable::from_numbers(numbers);
+        let indices: HashMap<_, _> = table
+            .symbols
+            .iter()
+            .enumerate()
+            .map(|(index, &number)| (number, index))
+            .collect();
+
+        let mut state = STATE_LOW;
+        let mut words = vec![];
+
+        // Numbers are encoded backwards, so decoder gets them in order.
+        for number in numbers.iter().rev() {
+            let index = indices[number];
+            let frequency = table.frequencies[index];
+            let state_max = ((STATE_LOW >> table.scale_bits) << WORD_WIDTH) * frequency;
+
+            if state >= state_max {
+                words.push(state as u32);
+                state >>= WORD_WIDTH;
+            }
+
+            state = ((state / frequency) << table.scale_bits)
+                + state % frequency
+                + table.starts[index];
+        }
+
+        let header = table.encode_header(numbers.len());
+
+        let mut bits = Bits::new();
+        bits.push_number(header.len(), HEADER_LEN_WIDTH);
+        bits.append_bits(&header);
+        bits.push_number(state as usize, STATE_WIDTH);
+
+        for &word in words.iter().rev() {
+            bits.push_number(word as usize, WORD_WIDTH);
+        }
+
+        bits
+    }
+}
+
+/// Static rANS decoder.
+pub struct RansDecoder;
+
+impl NumberDecoder for RansDecoder {
+    fn decode(bits: &Bits) -> Vec<usize> {
+        let header_len: usize = bits.slice(0, HEADER_LEN_WIDTH).into();
+        let mut index = HEADER_LEN_WIDTH + header_len;
+
+        let (numbers_count, table) =
+            FrequencyTable::decode_header(&bits.slice(HEADER_LEN_WIDTH, index));
+
+        let mut read_number = |width: usize| {
+            let number: usize = bits.slice(index, index + width).into();
+            index += width;
+            number as u64
+        };
+
+        let mut state = read_number(STATE_WIDTH);
+        let mut numbers = Vec::with_capacity(numbers_count);
+
+        for _ in 0..numbers_count {
+            let slot = state & ((1 << table.scale_bits) - 1);
+            let index = table.find(slot);
+
+            state = table.frequencies[index] * (state >> table.scale_bits) + slot
+                - table.starts[index];
+
+            if state < STATE_LOW {
+                state = state << WORD_WIDTH | read_number(WORD_WIDTH);
+            }
+
+            numbers.push(table.symbols[index]);
+        }
+
+        numbers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frequency_table_works() {
+        let table = FrequencyTable::from_numbers(&[1, 1, 1, 3]);
+
+        assert_eq!(vec![1, 3], table.symbols);
+        assert_eq!(vec![3072, 1024], table.frequencies);
+        assert_eq!(vec![0, 3072], table.starts);
+        assert_eq!(0, table.find(3071));
+        assert_eq!(1, table.find(3072));
+    }
+
+    #[test]
+    fn decode_number_works() {
+        let numbers = [1, 2, 257, 259, 258, 2];
+
+        let encoded = RansEncoder::encode(&numbers);
+        let decoded = RansDecoder::decode(&encoded);
+
+        assert_eq!(vec![1, 2, 257, 259, 258, 2], decoded);
+    }
+
+    #[test]
+    fn skewed_numbers_work() {
+        // Many rare numbers rounded up to frequency 1 exceed the scale.
+        let mut numbers: Vec<_> = (0..100).flat_map(|n| [n; 10_000]).collect();
+        numbers.extend(100..1000);
+
+        let table = FrequencyTable::from_numbers(&numbers);
+
+        assert_eq!(1 << table.scale_bits, table.frequencies.iter().sum::<u64>());
+        assert!(table.frequencies.iter().all(|&f| f > 0));
+
+        let encoded = RansEncoder::encode(&numbers);
+
+        assert_eq!(numbers, RansDecoder::decode(&encoded));
+    }
+
+    #[test]
+    fn large_numbers_work() {
+        let numbers = [1, 5_000_000, usize::MAX, 1];
+
+        let encoded = RansEncoder::encode(&numbers);
+        let decoded = RansDecoder::decode(&encoded);
+
+        assert!(encoded.len() < 1000);
+        assert_eq!(vec![1, 5_000_000, usize::MAX, 1], decoded);
+    }
+
+    #[test]
+    fn empty_works() {
+        let encoded = RansEncoder::encode(&[]);
+
+        assert!(RansDecoder::decode(&encoded).is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+//! Things for writing numbers as raw bytes.
+
+use crate::bits::{Bit, Bits};
+use crate::number_encoders::{NumberDecoder, NumberEncoder};
+
+/// Encoder writing each number from `1..=256` as one byte.
+/// Numbers are shifted down by one, so it can be used in place
+/// of elias encoders which can't handle 0.
+pub struct RawByteEncoder;
+
+impl NumberEncoder for RawByteEncoder {
+    fn encode(numbers: &[usize]) -> Bits {
+        let mut bits = Bits::new();
+
+        for &number in numbers {
+            if number == 0 || number > 256 {
+                panic!("number doesn't fit in one byte");
+            }
+
+            bits.push_number(number - 1, 8);
+        }
+
+        bits
+    }
+}
+
+/// Decoder reading bytes written by `RawByteEncoder`.
+pub struct RawByteDecoder;
+
+impl NumberDecoder for RawByteDecoder {
+    fn decode(bits: &Bits) -> Vec<usize> {
+        let mut numbers = vec![];
+
+        let mut number = 0;
+        let mut number_len = 0;
+
+        for bit in bits.iter() {
+            number <<= 1;
+            number_len += 1;
+
+            if let Bit::ONE = bit {
+                number |= 1;
+            }
+
+            if number_len == 8 {
+                numbers.push(number + 1);
+                number = 0;
+                number_len = 0;
+            }
+        }
+
+        numbers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_works() {
+        let numbers = [1, 138, 256];
+
+        let bits = RawByteEncoder::encode(&numbers);
+
+        assert_eq!([0b00000000, 0b10001001, 0b11111111], bits.get_bits());
+    }
+
+    #[test]
+    fn decode_number_works() {
+        let numbers = [1, 2, 137, 255, 256];
+
+        let encoded = RawByteEncoder::encode(&numbers);
+        let decoded = RawByteDecoder::decode(&encoded);
+
+        assert_eq!(vec![1, 2, 137, 255, 256], decoded);
+    }
+}
@@ -0,0 +1,197 @@
+//! Things for implementing Simple-9 and Simple-16 word-aligned encodings.
+
+use crate::bits::Bits;
+use crate::number_encoders::{NumberDecoder, NumberEncoder};
+
+const COUNT_BYTES: usize = 8;
+const WORD_BYTES: usize = 4;
+const DATA_WIDTH: u32 = 28;
+
+/// The biggest number Simple-9 and Simple-16 can encode.
+pub const SIMPLE_MAX_NUMBER: usize = (1 << DATA_WIDTH) - 1;
+
+/// Widths of numbers packed in 28 data bits, for every Simple-9 selector.
+const SIMPLE_9: [&[u32]; 9] = [
+    &[1; 28],
+    &[2; 14],
+    &[3; 9],
+    &[4; 7],
+    &[5; 5],
+    &[7; 4],
+    &[9; 3],
+    &[14; 2],
+    &[28],
+];
+
+/// Widths of numbers packed in 28 data bits, for every Simple-16 selector.
+/// Unlike Simple-9, numbers in one word can have different widths.
+const SIMPLE_16: [&[u32]; 16] = [
+    &[1; 28],
+    &[2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+    &[1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1],
+    &[1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2],
+    &[2; 14],
+    &[4, 3, 3, 3, 3, 3, 3, 3, 3],
+    &[3, 4, 4, 4, 4, 3, 3, 3],
+    &[4; 7],
+    &[5, 5, 5, 5, 4, 4],
+    &[4, 4, 5, 5, 5, 5],
+    &[6, 6, 6, 5, 5],
+    &[5, 5, 6, 6, 6],
+    &[7; 4],
+    &[10, 9, 9],
+    &[14; 2],
+    &[28],
+];
+
+/// Simple-9 encoder. Numbers are packed into 32 bit words, every word
+/// starts with 4 bit selector telling how many numbers it holds.
+/// Numbers have to be smaller than 2^28.
+pub struct Simple9Encoder;
+
+impl NumberEncoder for Simple9Encoder {
+    fn encode(numbers: &[usize]) -> Bits {
+        encode_words(numbers, &SIMPLE_9)
+    }
+}
+
+/// Simple-9 decoder.
+pub struct Simple9Decoder;
+
+impl NumberDecoder for Simple9Decoder {
+    fn decode(bits: &Bits) -> Vec<usize> {
+        decode_words(bits, &SIMPLE_9)
+    }
+}
+
+/// Simple-16 encoder. Works like Simple-9 with more ways to fill a word.
+/// Numbers have to be smaller than 2^28.
+pub struct Simple16Encoder;
+
+impl NumberEncoder for Simple16Encoder {
+    fn encode(numbers: &[usize]) -> Bits {
+        encode_words(numbers, &SIMPLE_16)
+    }
+}
+
+/// Simple-16 decoder.
+pub struct Simple16Decoder;
+
+impl NumberDecoder for Simple16Decoder {
+    fn decode(bits: &Bits) -> Vec<usize> {
+        decode_words(bits, &SIMPLE_16)
+    }
+}
+
+/// Writes numbers count followed by words. Every word uses the first
+/// selector which fits following numbers, selectors are ordered from
+/// the one holding the most numbers.
+fn encode_words(numbers: &[usize], selectors: &[&[u32]]) -> Bits {
+    let mut bytes = (numbers.len() as u64).to_be_bytes().to_vec();
+    let mut rest = numbers;
+
+    while !rest.is_empty() {
+        let (selector, widths) = selectors
+            .iter()
+            .enumerate()
+            .find(|(_, widths)| {
+                widths
+                    .iter()
+                    .zip(rest)
+                    .all(|(&width, &number)| number >> width == 0)
+            })
+            .expect("number too big for simple encoding");
+
+        let mut word = (selector as u32) << DATA_WIDTH;
+        let mut shift = DATA_WIDTH;
+
+        // The last word is padded with zeros, count tells where numbers end.
+        for (&width, &number) in widths.iter().zip(rest) {
+            shift -= width;
+            word |= (number as u32) << shift;
+        }
+
+        bytes.extend_from_slice(&word.to_be_bytes());
+        rest = &rest[widths.len().min(rest.len())..];
+    }
+
+    Bits::from_vec(bytes.len() * 8, bytes)
+}
+
+fn decode_words(bits: &Bits, selectors: &[&[u32]]) -> Vec<usize> {
+    let (count, words) = bits.get_bits().split_at(COUNT_BYTES);
+    let count = u64::from_be_bytes(count.try_into().unwrap()) as usize;
+
+    let mut numbers = Vec::with_capacity(count);
+
+    for word in words.chunks_exact(WORD_BYTES) {
+        let word = u32::from_be_bytes(word.try_into().unwrap());
+        let mut shift = DATA_WIDTH;
+
+        for &width in selectors[(word >> DATA_WIDTH) as usize] {
+            shift -= width;
+            numbers.push((word >> shift & ((1 << width) - 1)) as usize);
+        }
+    }
+
+    numbers.truncate(count);
+
+    numbers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selectors_fit_word() {
+        for widths in SIMPLE_9.iter().chain(&SIMPLE_16) {
+            assert!(widths.iter().sum::<u32>() <= DATA_WIDTH);
+        }
+
+        // Simple-16 doesn't waste any bits.
+        for widths in SIMPLE_16 {
+            assert_eq!(DATA_WIDTH, widths.iter().sum::<u32>());
+        }
+    }
+
+    #[test]
+    fn simple_9_works() {
+        let numbers = [1; 28];
+
+        let bits = Simple9Encoder::encode(&numbers);
+
+        assert_eq!(8 * (COUNT_BYTES + WORD_BYTES), bits.len());
+        assert_eq!(numbers.to_vec(), Simple9Decoder::decode(&bits));
+    }
+
+    #[test]
+    fn decode_number_works() {
+        let numbers: Vec<_> = (0..1000).map(|n| n * n % 700 + n % 3 * 100_000).collect();
+
+        let simple_9 = Simple9Encoder::encode(&numbers);
+        let simple_16 = Simple16Encoder::encode(&numbers);
+
+        assert_eq!(numbers, Simple9Decoder::decode(&simple_9));
+        assert_eq!(numbers, Simple16Decoder::decode(&simple_16));
+        assert!(simple_16.len() <= simple_9.len());
+        assert!(Simple16Decoder::decode(&Simple16Encoder::encode(&[])).is_empty());
+    }
+
+    #[test]
+    fn mixed_widths_works() {
+        // One 4 bit number followed by eight 3 bit ones fit in one Simple-16 word.
+        let numbers = [15, 7, 7, 7, 7, 7, 7, 7, 7];
+
+        let bits = Simple16Encoder::encode(&numbers);
+
+        assert_eq!(8 * (COUNT_BYTES + WORD_BYTES), bits.len());
+        assert_eq!(numbers.to_vec(), Simple16Decoder::decode(&bits));
+    }
+
+    #[test]
+    #[should_panic(expected = "number too big for simple encoding")]
+    fn too_big_number_panics() {
+        Simple9Encoder::encode(&[1 << 28]);
+    }
+}
@@ -0,0 +1,181 @@
+//! Things for implementing truncated binary (also called minimal binary
+//! or phase-in) encoding of numbers from known range.
+
+use crate::bits::{self, Bit, Bits};
+use crate::number_encoders::{
+    AdaptiveNumberDecoder, AdaptiveNumberEncoder, NumberDecoder, NumberEncoder,
+};
+
+const HEADER_WIDTH: usize = 64;
+
+/// Encodes `number` from range [0, `range`) with truncated binary code.
+/// Its first numbers take one bit less than the rest, so at most one bit
+/// is wasted compared with `log2(range)`. Range of size 1 takes no bits.
+pub fn encode_number(number: usize, range: usize, bits: &mut Bits) {
+    let width = bits::get_usize_bit_len(range) - 1;
+    let short_count = short_count(width, range);
+
+    match number < short_count {
+        true => bits.push_number(number, width),
+        false => bits.push_number(number + short_count, width + 1),
+    }
+}
+
+/// Decodes number from range [0, `range`) starting at `position`,
+/// which is moved after its code.
+pub fn decode_number(range: usize, bits: &Bits, position: &mut usize) -> usize {
+    let width = bits::get_usize_bit_len(range) - 1;
+    let short_count = short_count(width, range);
+
+    let number = bits.get_number(*position, width);
+    *position += width;
+
+    if number < short_count {
+        return number;
+    }
+
+    let number = match bits.get_bit(*position) {
+        Bit::ONE => number << 1 | 1,
+        Bit::ZERO => number << 1,
+    };
+    *position += 1;
+
+    number - short_count
+}
+
+/// Returns count of `width` bits long codes. Range of 2^63 or more
+/// has codes of 64 bits, so the shift is done on `u128`.
+fn short_count(width: usize, range: usize) -> usize {
+    ((1u128 << (width + 1)) - range as u128) as usize
+}
+
+/// Truncated binary encoder. As `NumberEncoder` it writes numbers count
+/// and range given by the biggest number in front of codes. As
+/// `AdaptiveNumberEncoder` every number uses its bound as range,
+/// e.g. LZW codes are bounded by current dictionary size.
+#[derive(Default)]
+pub struct TruncatedBinaryEncoder {
+    bits: Bits,
+}
+
+impl NumberEncoder for TruncatedBinaryEncoder {
+    fn encode(numbers: &[usize]) -> Bits {
+        let range = numbers.iter().max().map_or(0, |max| {
+            max.checked_add(1).expect("number too big for truncated binary encoding")
+        });
+
+        let mut bits = Bits::new();
+        bits.push_number(numbers.len(), HEADER_WIDTH);
+        bits.push_number(range, HEADER_WIDTH);
+
+        for &number in numbers {
+            encode_number(number, range, &mut bits);
+        }
+
+        bits
+    }
+}
+
+impl AdaptiveNumberEncoder for TruncatedBinaryEncoder {
+    fn new() -> Self {
+        TruncatedBinaryEncoder::default()
+    }
+
+    fn encode_number(&mut self, number: usize, bound: usize) {
+        encode_number(number, bound, &mut self.bits);
+    }
+
+    fn finish(self) -> Bits {
+        self.bits
+    }
+}
+
+/// Truncated binary decoder.
+pub struct TruncatedBinaryDecoder {
+    bits: Bits,
+    position: usize,
+}
+
+impl NumberDecoder for TruncatedBinaryDecoder {
+    fn decode(bits: &Bits) -> Vec<usize> {
+        let count = bits.get_number(0, HEADER_WIDTH);
+        let range = bits.get_number(HEADER_WIDTH, HEADER_WIDTH);
+
+        let mut position = 2 * HEADER_WIDTH;
+
+        (0..count)
+            .map(|_| decode_number(range, bits, &mut position))
+            .collect()
+    }
+}
+
+impl AdaptiveNumberDecoder for TruncatedBinaryDecoder {
+    fn new(bits: Bits) -> Self {
+        TruncatedBinaryDecoder { bits, position: 0 }
+    }
+
+    fn decode_number(&mut self, bound: usize) -> usize {
+        decode_number(bound, &self.bits, &mut self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncated_binary_works() {
+        let mut bits = Bits::new();
+
+        for number in 0..5 {
+            encode_number(number, 5, &mut bits);
+        }
+        encode_number(0, 1, &mut bits);
+
+        // 00, 01, 10, 110, 111
+        assert_eq!(12, bits.len());
+
+        let mut position = 0;
+        let decoded: Vec<_> = (0..5)
+            .map(|_| decode_number(5, &bits, &mut position))
+            .collect();
+
+        assert_eq!(vec![0, 1, 2, 3, 4], decoded);
+    }
+
+    #[test]
+    fn decode_number_works() {
+        let numbers = [0, 4, 9, 2, 2, 7];
+
+        let encoded = TruncatedBinaryEncoder::encode(&numbers);
+
+        // Range 10 has 6 codes of 3 bits and 4 codes of 4 bits.
+        assert_eq!(2 * HEADER_WIDTH + 3 + 3 + 4 + 3 + 3 + 4, encoded.len());
+        assert_eq!(numbers.to_vec(), TruncatedBinaryDecoder::decode(&encoded));
+        assert!(TruncatedBinaryDecoder::decode(&TruncatedBinaryEncoder::encode(&[])).is_empty());
+    }
+
+    #[test]
+    fn big_range_works() {
+        for numbers in [[1 << 63, 0], [usize::MAX - 1, 0], [usize::MAX - 1, (1 << 63) + 5]] {
+            let encoded = TruncatedBinaryEncoder::encode(&numbers);
+
+            assert_eq!(numbers.to_vec(), TruncatedBinaryDecoder::decode(&encoded));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "number too big for truncated binary encoding")]
+    fn max_number_panics() {
+        TruncatedBinaryEncoder::encode(&[usize::MAX]);
+    }
+
+    #[test]
+    fn lzw_works() {
+        let data = b"abracadabra abracadabra abracadabra";
+
+        let encoded = crate::encode_adaptive::<TruncatedBinaryEncoder>(data);
+
+        assert_eq!(data.to_vec(), crate::decode_adaptive::<TruncatedBinaryDecoder>(&encoded));
+    }
+}
@@ -0,0 +1,108 @@
+//! Statistics of compression gathered while encoding.
+
+use std::time::Duration;
+
+/// Statistics of data compressed with LZW followed by numbers encoding.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompressionStats {
+    /// Length of uncompressed data in bytes.
+    pub data_len: usize,
+    /// Length of compressed code in bits.
+    pub code_len: usize,
+    /// Number of codes emitted by LZW.
+    pub codes_count: usize,
+    /// Number of words in LZW dictionary after encoding.
+    pub dictionary_size: usize,
+    /// Mean value of codes emitted by LZW.
+    pub average_code: f64,
+    /// Order-0 entropy of LZW codes, lower bound of bits per code.
+    pub codes_entropy: f64,
+    /// Time spent building dictionary and emitting LZW codes.
+    pub dictionary_time: Duration,
+    /// Time spent encoding LZW codes into bits.
+    pub encoding_time: Duration,
+}
+
+impl CompressionStats {
+    /// Creates new instance of `CompressionStats` for `data_len` bytes
+    /// compressed into `code_len` bits through LZW `codes`.
+    pub fn new(
+        data_len: usize,
+        code_len: usize,
+        codes: &[usize],
+        dictionary_size: usize,
+        dictionary_time: Duration,
+        encoding_time: Duration,
+    ) -> CompressionStats {
+        let average_code = match codes.len() {
+            0 => 0.0,
+            count => codes.iter().sum::<usize>() as f64 / count as f64,
+        };
+
+        CompressionStats {
+            data_len,
+            code_len,
+            codes_count: codes.len(),
+            dictionary_size,
+            average_code,
+            codes_entropy: crate::stats::entropy(codes),
+            dictionary_time,
+            encoding_time,
+        }
+    }
+
+    /// Returns length of compressed code in whole bytes.
+    pub fn code_bytes(&self) -> usize {
+        self.code_len / 8
+    }
+
+    /// Returns compressed length divided by uncompressed length.
+    pub fn compression_ratio(&self) -> f64 {
+        self.code_bytes() as f64 / self.data_len as f64
+    }
+
+    /// Returns average number of bits used to encode one LZW code.
+    pub fn bits_per_code(&self) -> f64 {
+        match self.codes_count {
+            0 => 0.0,
+            count => self.code_len as f64 / count as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EliasGammaEncoder, NumberEncoder};
+
+    #[test]
+    fn compression_stats_works() {
+        let stats = CompressionStats::new(
+            10,
+            48,
+            &[97, 98, 258, 99],
+            261,
+            Duration::ZERO,
+            Duration::ZERO,
+        );
+
+        assert_eq!(4, stats.codes_count);
+        assert_eq!(138.0, stats.average_code);
+        assert_eq!(2.0, stats.codes_entropy);
+        assert_eq!(12.0, stats.bits_per_code());
+        assert_eq!(0.6, stats.compression_ratio());
+    }
+
+    #[test]
+    fn encode_with_stats_works() {
+        let data = b"abababab";
+
+        let (bits, stats) = crate::encode_with_stats(data, EliasGammaEncoder::encode);
+
+        assert_eq!(crate::encode::<EliasGammaEncoder>(data).get_bits(), bits.get_bits());
+        assert_eq!(data.len(), stats.data_len);
+        assert_eq!(bits.len(), stats.code_len);
+        assert_eq!(5, stats.codes_count);
+        assert_eq!(256 + 4, stats.dictionary_size);
+    }
+}
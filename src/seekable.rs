@@ -0,0 +1,176 @@
+//! Things usefull for reading part of blocks container without decoding it whole.
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::bits::Bits;
+use crate::blocks::BlockIndex;
+use crate::number_encoders::NumberDecoder;
+
+/// Decompresses blocks container written by `blocks::write_blocks` on demand.
+/// Only blocks covering read bytes are read from `reader` and decoded.
+pub struct SeekableDecompressor<R> {
+    reader: R,
+    start: u64,
+    index: BlockIndex,
+    position: usize,
+    current_block: Option<(usize, Vec<u8>)>,
+    decode: fn(&Bits) -> Vec<usize>,
+}
+
+impl<R> SeekableDecompressor<R>
+where
+    R: Read + Seek,
+{
+    /// Creates new instance of `SeekableDecompressor` reading blocks index
+    /// from the end of `reader`. Container starts at current position
+    /// of `reader`, so it can be preceded e.g. by a header.
+    pub fn new<D>(reader: R) -> io::Result<SeekableDecompressor<R>>
+    where
+        D: NumberDecoder,
+    {
+        SeekableDecompressor::with_decode(reader, D::decode)
+    }
+
+    /// Works like `new`, but decoder can be chosen at runtime.
+    pub fn with_decode(
+        mut reader: R,
+        decode: fn(&Bits) -> Vec<usize>,
+    ) -> io::Result<SeekableDecompressor<R>> {
+        let start = reader.stream_position()?;
+        let index = BlockIndex::read(&mut reader)?;
+
+        Ok(SeekableDecompressor {
+            reader,
+            start,
+            index,
+            position: 0,
+            current_block: None,
+            decode,
+        })
+    }
+
+    /// Returns length of uncompressed data.
+    pub fn len(&self) -> usize {
+        self.index.data_len
+    }
+
+    /// Checks if uncompressed data is empty.
+    pub fn is_empty(&self) -> bool {
+        self.index.data_len == 0
+    }
+
+    /// Decodes `block` unless it's the last decoded one.
+    fn load_block(&mut self, block: usize) -> io::Result<&[u8]> {
+        if !matches!(self.current_block, Some((b, _)) if b == block) {
+            let bit_range = self.index.bit_range(block);
+
+            let first_byte = bit_range.start / 8;
+            let mut bytes = vec![0; bit_range.end.div_ceil(8) - first_byte];
+
+            self.reader.seek(SeekFrom::Start(self.start + first_byte as u64))?;
+            self.reader.read_exact(&mut bytes)?;
+
+            let first_bit = bit_range.start % 8;
+            let bits = Bits::from_vec(bytes.len() * 8, bytes);
+            let bits = bits.slice(first_bit, first_bit + bit_range.len());
+
+            self.current_block = Some((block, crate::decode_with(&bits, self.decode)));
+        }
+
+        Ok(&self.current_block.as_ref().unwrap().1)
+    }
+}
+
+impl<R> Read for SeekableDecompressor<R>
+where
+    R: Read + Seek,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let block = match self.index.find_block(self.position) {
+            Some(block) => block,
+            None => return Ok(0),
+        };
+
+        let block_start = self.index.data_range(block).start;
+        let position = self.position - block_start;
+
+        let data = &self.load_block(block)?[position..];
+        let len = data.len().min(buf.len());
+
+        buf[..len].copy_from_slice(&data[..len]);
+        self.position += len;
+
+        Ok(len)
+    }
+}
+
+impl<R> Seek for SeekableDecompressor<R>
+where
+    R: Read + Seek,
+{
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset as i64),
+            SeekFrom::End(offset) => (self.index.data_len as i64).checked_add(offset),
+            SeekFrom::Current(offset) => (self.position as i64).checked_add(offset),
+        };
+
+        match position {
+            Some(position) if position >= 0 => {
+                self.position = position as usize;
+                Ok(position as u64)
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::blocks;
+    use crate::{EliasOmegaDecoder, EliasOmegaEncoder};
+
+    #[test]
+    fn seek_and_read_works() {
+        let data: Vec<_> = (0..10_000).map(|n| (n % 7 * n % 13) as u8).collect();
+
+        let blocks = blocks::compress_blocks::<EliasOmegaEncoder>(&data, 1000, 2);
+        let bytes = blocks::write_blocks(&blocks);
+
+        let mut decompressor =
+            SeekableDecompressor::new::<EliasOmegaDecoder>(Cursor::new(bytes)).unwrap();
+
+        let mut read = vec![0; 2500];
+        decompressor.seek(SeekFrom::Start(3500)).unwrap();
+        decompressor.read_exact(&mut read).unwrap();
+
+        assert_eq!(10_000, decompressor.len());
+        assert_eq!(&data[3500..6000], &read);
+    }
+
+    #[test]
+    fn read_to_end_works() {
+        let data = b"abracadabra abracadabra".to_vec();
+
+        let blocks = blocks::compress_blocks::<EliasOmegaEncoder>(&data, 10, 2);
+        let mut bytes = b"header".to_vec();
+        bytes.append(&mut blocks::write_blocks(&blocks));
+
+        let mut reader = Cursor::new(bytes);
+        reader.seek(SeekFrom::Start(6)).unwrap();
+
+        let mut decompressor = SeekableDecompressor::new::<EliasOmegaDecoder>(reader).unwrap();
+
+        let mut read = vec![];
+        decompressor.seek(SeekFrom::End(-15)).unwrap();
+        decompressor.read_to_end(&mut read).unwrap();
+
+        assert_eq!(&data[8..], &read);
+    }
+}
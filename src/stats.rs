@@ -0,0 +1,129 @@
+//! Things usefull for computing entropy and empirical model statistics
+//! of data kept in memory.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::lzw::lzw_encoder::LzwEncoder;
+use crate::number_encoders::CodeLength;
+
+/// Computes order-0 entropy of `symbols` in bits per symbol.
+pub fn entropy<T>(symbols: &[T]) -> f64
+where
+    T: Hash + Eq,
+{
+    conditional_entropy(symbols, 0)
+}
+
+/// Computes order-`order` conditional entropy of `symbols` in bits per symbol,
+/// i.e. entropy of symbol given `order` symbols preceding it. First `order`
+/// symbols have no full context, so they are skipped.
+pub fn conditional_entropy<T>(symbols: &[T], order: usize) -> f64
+where
+    T: Hash + Eq,
+{
+    if symbols.len() <= order {
+        return 0.0;
+    }
+
+    let mut contexts: HashMap<&[T], usize> = HashMap::new();
+    let mut words: HashMap<&[T], usize> = HashMap::new();
+
+    for word in symbols.windows(order + 1) {
+        *contexts.entry(&word[..order]).or_default() += 1;
+        *words.entry(word).or_default() += 1;
+    }
+
+    let total = (symbols.len() - order) as f64;
+
+    let mut terms: Vec<_> = words
+        .iter()
+        .map(|(word, &count)| {
+            let count = count as f64;
+            let context_count = contexts[&word[..order]] as f64;
+
+            -count / total * (count / context_count).log2()
+        })
+        .collect();
+
+    // Summed in fixed order, so result doesn't depend on order of `HashMap`.
+    terms.sort_by(f64::total_cmp);
+    terms.iter().sum()
+}
+
+/// Average length of universal codes of LZW codes compared
+/// with order-0 entropy of these codes, which bounds it from below.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CodeEfficiency {
+    pub codes_count: usize,
+    pub average_code_len: f64,
+    pub entropy: f64,
+}
+
+impl CodeEfficiency {
+    /// Returns number of bits per code wasted above entropy bound.
+    pub fn redundancy(&self) -> f64 {
+        self.average_code_len - self.entropy
+    }
+}
+
+/// Encodes `data` with LZW and computes how close code `E` gets to entropy of LZW codes.
+pub fn lzw_code_efficiency<E>(data: &[u8]) -> CodeEfficiency
+where
+    E: CodeLength,
+{
+    let codes = LzwEncoder::new().encode_text(data);
+
+    // Needed because elias can't handle 0.
+    let code_len: usize = codes.iter().map(|&code| E::code_len(code + 1)).sum();
+
+    let average_code_len = match codes.len() {
+        0 => 0.0,
+        count => code_len as f64 / count as f64,
+    };
+
+    CodeEfficiency {
+        codes_count: codes.len(),
+        average_code_len,
+        entropy: entropy(&codes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EliasGammaEncoder, EliasOmegaEncoder};
+
+    #[test]
+    fn entropy_works() {
+        assert_eq!(0.0, entropy::<u8>(&[]));
+        assert_eq!(0.0, entropy(b"aaaa"));
+        assert_eq!(1.0, entropy(b"abab"));
+        assert_eq!(2.0, entropy(b"abcdabcd"));
+        assert_eq!(1.5, entropy(&[1, 1, 2, 3]));
+    }
+
+    #[test]
+    fn conditional_entropy_works() {
+        // Every symbol is determined by the previous one.
+        assert_eq!(0.0, conditional_entropy(b"abababab", 1));
+        assert_eq!(1.0, conditional_entropy(b"abababab", 0));
+
+        // After `a` comes `a` or `b` equally often, after `b` always `a`.
+        assert_eq!(0.75, conditional_entropy(b"aabaabaab", 1));
+        assert_eq!(0.0, conditional_entropy(b"ab", 2));
+    }
+
+    #[test]
+    fn lzw_code_efficiency_works() {
+        let data = b"abracadabra abracadabra abracadabra";
+
+        let gamma = lzw_code_efficiency::<EliasGammaEncoder>(data);
+        let omega = lzw_code_efficiency::<EliasOmegaEncoder>(data);
+
+        assert_eq!(gamma.codes_count, omega.codes_count);
+        assert_eq!(gamma.entropy, omega.entropy);
+        assert!(gamma.redundancy() > 0.0);
+        assert!(omega.average_code_len < gamma.average_code_len);
+    }
+}
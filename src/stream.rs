@@ -0,0 +1,188 @@
+//! Things usefull for compressing streams, which don't have to fit in memory.
+//!
+//! Data is compressed in blocks written as soon as they are ready. Every
+//! block is preceded by its uncompressed length, CRC-32 and compressed
+//! length. Block of length 0 ends the stream.
+
+use std::io::{self, Read, Write};
+
+use crate::io_utils::{self, crc32, invalid_data, USIZE_LEN};
+
+const FRAME_HEADER_LEN: usize = 2 * USIZE_LEN + 4;
+
+/// Compresses data read from `reader` in blocks of `block_size` bytes with
+/// `compress` and writes them to `writer`. Returns length of read data
+/// and number of written bytes.
+pub fn compress_stream<R, W, F>(
+    reader: &mut R,
+    writer: &mut W,
+    block_size: usize,
+    compress: F,
+) -> io::Result<(usize, usize)>
+where
+    R: Read,
+    W: Write,
+    F: Fn(&[u8]) -> Vec<u8>,
+{
+    assert!(block_size > 0, "block size has to be positive");
+
+    let mut data_len = 0;
+    let mut written = 0;
+
+    loop {
+        let mut block = vec![];
+        reader.take(block_size as u64).read_to_end(&mut block)?;
+
+        let compressed = match block.is_empty() {
+            true => vec![],
+            false => compress(&block),
+        };
+
+        let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + compressed.len());
+        frame.extend_from_slice(&block.len().to_be_bytes());
+        frame.extend_from_slice(&crc32(&block).to_be_bytes());
+        frame.extend_from_slice(&compressed.len().to_be_bytes());
+        frame.extend_from_slice(&compressed);
+
+        writer.write_all(&frame)?;
+
+        data_len += block.len();
+        written += frame.len();
+
+        if block.is_empty() {
+            break;
+        }
+    }
+
+    writer.flush()?;
+
+    Ok((data_len, written))
+}
+
+/// Decompresses blocks read from `reader` with `decompress` and writes
+/// every one of them to `writer` after checking its length and checksum.
+/// Returns length of written data.
+pub fn decompress_stream<R, W, F>(reader: &mut R, writer: &mut W, decompress: F) -> io::Result<usize>
+where
+    R: Read,
+    W: Write,
+    F: Fn(&[u8]) -> Vec<u8>,
+{
+    let mut data_len = 0;
+
+    loop {
+        let mut frame_header = [0; FRAME_HEADER_LEN];
+        reader.read_exact(&mut frame_header)?;
+
+        let mut frame_header = &frame_header[..];
+        let block_len = io_utils::read_usize(&mut frame_header)?;
+        let checksum = io_utils::read_u32(&mut frame_header)?;
+        let compressed_len = io_utils::read_usize(&mut frame_header)?;
+
+        if block_len == 0 {
+            break;
+        }
+
+        // Length isn't trusted for allocation, only available bytes are read.
+        let mut compressed = vec![];
+        reader.take(compressed_len as u64).read_to_end(&mut compressed)?;
+
+        if compressed.len() != compressed_len {
+            return Err(invalid_data("stream is truncated"));
+        }
+
+        let block = decompress(&compressed);
+
+        if block.len() != block_len || crc32(&block) != checksum {
+            return Err(invalid_data("checksum of decompressed block doesn't match"));
+        }
+
+        writer.write_all(&block)?;
+        data_len += block.len();
+    }
+
+    writer.flush()?;
+
+    Ok(data_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EliasGammaDecoder, EliasGammaEncoder};
+
+    // Bits length is stored too, so padding of the last byte isn't decoded.
+    fn compress(block: &[u8]) -> Vec<u8> {
+        let bits = crate::encode::<EliasGammaEncoder>(block);
+
+        let mut bytes = bits.len().to_be_bytes().to_vec();
+        bytes.extend_from_slice(bits.get_bits());
+
+        bytes
+    }
+
+    fn decompress(bytes: &[u8]) -> Vec<u8> {
+        let (len, bytes) = bytes.split_at(USIZE_LEN);
+        let len = usize::from_be_bytes(len.try_into().unwrap());
+
+        crate::decode::<EliasGammaDecoder>(&crate::bits::Bits::from_vec(len, bytes.to_vec()))
+    }
+
+    #[test]
+    fn stream_roundtrip_works() {
+        let data: Vec<_> = (0..10_000).map(|n| (n % 7 * n % 13) as u8).collect();
+
+        let mut compressed = vec![];
+        let (data_len, written) =
+            compress_stream(&mut &data[..], &mut compressed, 1000, compress).unwrap();
+
+        assert_eq!(data.len(), data_len);
+        assert_eq!(compressed.len(), written);
+
+        let mut decompressed = vec![];
+        let decompressed_len =
+            decompress_stream(&mut &compressed[..], &mut decompressed, decompress).unwrap();
+
+        assert_eq!(data.len(), decompressed_len);
+        assert_eq!(data, decompressed);
+    }
+
+    #[test]
+    fn empty_stream_works() {
+        let mut compressed = vec![];
+        compress_stream(&mut &b""[..], &mut compressed, 10, compress).unwrap();
+
+        assert_eq!(FRAME_HEADER_LEN, compressed.len());
+
+        let mut decompressed = vec![];
+        decompress_stream(&mut &compressed[..], &mut decompressed, decompress).unwrap();
+
+        assert!(decompressed.is_empty());
+    }
+
+    #[test]
+    fn corrupted_block_is_detected() {
+        let mut compressed = vec![];
+        compress_stream(&mut &b"abracadabra"[..], &mut compressed, 4, compress).unwrap();
+
+        // Checksum of the first block.
+        compressed[USIZE_LEN] ^= 1;
+
+        let result = decompress_stream(&mut &compressed[..], &mut vec![], decompress);
+
+        assert_eq!(io::ErrorKind::InvalidData, result.unwrap_err().kind());
+    }
+
+    #[test]
+    fn huge_block_length_is_rejected() {
+        let mut compressed = vec![];
+        compress_stream(&mut &b"abracadabra"[..], &mut compressed, 4, compress).unwrap();
+
+        // Compressed length of the first block.
+        compressed[USIZE_LEN + 4..FRAME_HEADER_LEN].copy_from_slice(&usize::MAX.to_be_bytes());
+
+        let result = decompress_stream(&mut &compressed[..], &mut vec![], decompress);
+
+        assert_eq!(io::ErrorKind::InvalidData, result.unwrap_err().kind());
+    }
+}
@@ -0,0 +1,330 @@
+//! Things usefull for transforming numbers before encoding them, so e.g.
+//! monotone counters and timestamps turn into small numbers.
+//!
+//! Applied transforms are recorded in `header::Header` of compressed files.
+//! Standalone streams of `encode_transformed` write them in front of numbers.
+
+use std::io;
+
+use crate::bits::Bits;
+use crate::number_encoders::{NumberDecoder, NumberEncoder};
+
+const COUNT_WIDTH: usize = 8;
+const TAG_WIDTH: usize = 8;
+const REFERENCE_WIDTH: usize = 64;
+
+/// Reversible transform of numbers. Differences wrap around, so numbers
+/// don't have to be increasing, but decreasing ones turn into huge numbers
+/// unless followed by `ZigZag`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    /// Replaces every number with its difference from the previous one.
+    Delta,
+    /// Applies `Delta` twice, so numbers growing by the same step turn into zeros.
+    DeltaOfDelta,
+    /// Maps numbers read as signed to unsigned ones: 0, -1, 1, -2, ... to 0, 1, 2, 3, ...
+    ZigZag,
+    /// Replaces every number with its xor with the previous one.
+    Xor,
+    /// Subtracts the smallest number, which is written in header.
+    FrameOfReference,
+}
+
+/// All transforms, position in this list is written in header as transform tag.
+const TRANSFORMS: [Transform; 5] = [
+    Transform::Delta,
+    Transform::DeltaOfDelta,
+    Transform::ZigZag,
+    Transform::Xor,
+    Transform::FrameOfReference,
+];
+
+impl Transform {
+    /// Transforms `numbers` in place. Returns reference number
+    /// needed to invert transform, 0 when it isn't needed.
+    pub fn apply(self, numbers: &mut [usize]) -> usize {
+        match self {
+            Transform::Delta => {
+                let mut previous = 0;
+
+                for number in numbers {
+                    let delta = number.wrapping_sub(previous);
+                    previous = std::mem::replace(number, delta);
+                }
+            }
+            Transform::DeltaOfDelta => {
+                Transform::Delta.apply(numbers);
+                Transform::Delta.apply(numbers);
+            }
+            Transform::ZigZag => {
+                for number in numbers {
+                    let signed = *number as isize;
+                    *number = ((signed << 1) ^ (signed >> (isize::BITS - 1))) as usize;
+                }
+            }
+            Transform::Xor => {
+                let mut previous = 0;
+
+                for number in numbers {
+                    let xor = *number ^ previous;
+                    previous = std::mem::replace(number, xor);
+                }
+            }
+            Transform::FrameOfReference => {
+                let reference = numbers.iter().copied().min().unwrap_or(0);
+
+                for number in numbers {
+                    *number -= reference;
+                }
+
+                return reference;
+            }
+        }
+
+        0
+    }
+
+    /// Inverts transform of `numbers` in place.
+    pub fn invert(self, numbers: &mut [usize], reference: usize) {
+        match self {
+            Transform::Delta => {
+                let mut previous: usize = 0;
+
+                for number in numbers {
+                    previous = previous.wrapping_add(*number);
+                    *number = previous;
+                }
+            }
+            Transform::DeltaOfDelta => {
+                Transform::Delta.invert(numbers, reference);
+                Transform::Delta.invert(numbers, reference);
+            }
+            Transform::ZigZag => {
+                for number in numbers {
+                    *number = (*number >> 1) ^ (*number & 1).wrapping_neg();
+                }
+            }
+            Transform::Xor => {
+                let mut previous = 0;
+
+                for number in numbers {
+                    previous ^= *number;
+                    *number = previous;
+                }
+            }
+            Transform::FrameOfReference => {
+                for number in numbers {
+                    *number += reference;
+                }
+            }
+        }
+    }
+
+    /// Returns tag of transform written in headers.
+    pub fn tag(self) -> usize {
+        TRANSFORMS
+            .iter()
+            .position(|&transform| transform == self)
+            .expect("all transforms are listed")
+    }
+
+    /// Returns transform written in headers as `tag`.
+    pub fn from_tag(tag: usize) -> Option<Transform> {
+        TRANSFORMS.get(tag).copied()
+    }
+}
+
+/// Applies `transforms` in order and encodes transformed numbers with `E`.
+/// Applied transforms are written first, so `decode_transformed` can invert
+/// them. Fails when transformed number is too big to be encoded, e.g. when
+/// decreasing numbers wrap around after `Delta` without `ZigZag`.
+pub fn encode_transformed<E>(numbers: &[usize], transforms: &[Transform]) -> io::Result<Bits>
+where
+    E: NumberEncoder,
+{
+    assert!(transforms.len() < 1 << COUNT_WIDTH, "too many transforms");
+
+    let mut bits = Bits::new();
+    bits.push_number(transforms.len(), COUNT_WIDTH);
+
+    for &transform in transforms {
+        bits.push_number(transform.tag(), TAG_WIDTH);
+    }
+
+    bits.append_bits(&encode_with_transforms(numbers, transforms, E::encode)?);
+
+    Ok(bits)
+}
+
+/// Decodes numbers encoded by `encode_transformed` and inverts transforms
+/// written in front of them.
+pub fn decode_transformed<D>(bits: &Bits) -> Vec<usize>
+where
+    D: NumberDecoder,
+{
+    let count = bits.get_number(0, COUNT_WIDTH);
+    let mut position = COUNT_WIDTH;
+
+    let transforms: Vec<_> = (0..count)
+        .map(|_| {
+            let tag = bits.get_number(position, TAG_WIDTH);
+            position += TAG_WIDTH;
+
+            Transform::from_tag(tag).expect("unknown transform tag")
+        })
+        .collect();
+
+    decode_with_transforms(&bits.slice(position, bits.len()), &transforms, D::decode)
+}
+
+/// Works like `encode_transformed`, but `transforms` aren't written, so they
+/// have to be recorded elsewhere, e.g. in `header::Header`. Only references
+/// of transforms are written in front of numbers encoded with `encode`.
+pub fn encode_with_transforms(
+    numbers: &[usize],
+    transforms: &[Transform],
+    encode: fn(&[usize]) -> Bits,
+) -> io::Result<Bits> {
+    let mut numbers = numbers.to_vec();
+    let mut bits = Bits::new();
+
+    for &transform in transforms {
+        let reference = transform.apply(&mut numbers);
+
+        if transform == Transform::FrameOfReference {
+            bits.push_number(reference, REFERENCE_WIDTH);
+        }
+    }
+
+    // Needed because elias can't handle 0.
+    let numbers = numbers
+        .iter()
+        .map(|number| number.checked_add(1))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "transformed number is too big, decreasing numbers need zigzag",
+            )
+        })?;
+
+    bits.append_bits(&encode(&numbers));
+
+    Ok(bits)
+}
+
+/// Decodes numbers encoded by `encode_with_transforms` with the same `transforms`.
+pub fn decode_with_transforms(
+    bits: &Bits,
+    transforms: &[Transform],
+    decode: fn(&Bits) -> Vec<usize>,
+) -> Vec<usize> {
+    let mut position = 0;
+
+    let references: Vec<_> = transforms
+        .iter()
+        .map(|&transform| match transform {
+            Transform::FrameOfReference => {
+                position += REFERENCE_WIDTH;
+                bits.get_number(position - REFERENCE_WIDTH, REFERENCE_WIDTH)
+            }
+            _ => 0,
+        })
+        .collect();
+
+    // Needed because elias can't handle 0.
+    let mut numbers: Vec<_> = decode(&bits.slice(position, bits.len()))
+        .into_iter()
+        .map(|number| number - 1)
+        .collect();
+
+    for (&transform, &reference) in transforms.iter().zip(&references).rev() {
+        transform.invert(&mut numbers, reference);
+    }
+
+    numbers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EliasGammaDecoder, EliasGammaEncoder, FibbonaciDecoder, FibbonaciEncoder};
+
+    #[test]
+    fn transforms_work() {
+        let cases = [
+            (Transform::Delta, vec![5, 7, 7, 10], vec![5, 2, 0, 3]),
+            (Transform::DeltaOfDelta, vec![10, 20, 30, 41], vec![10, 0, 0, 1]),
+            (Transform::ZigZag, vec![0, usize::MAX, 1, usize::MAX - 1], vec![0, 1, 2, 3]),
+            (Transform::Xor, vec![4, 5, 5, 1], vec![4, 1, 0, 4]),
+            (Transform::FrameOfReference, vec![1000, 1003, 1001], vec![0, 3, 1]),
+        ];
+
+        for (transform, numbers, expected) in cases {
+            let mut transformed = numbers.clone();
+            let reference = transform.apply(&mut transformed);
+
+            assert_eq!(expected, transformed);
+
+            transform.invert(&mut transformed, reference);
+
+            assert_eq!(numbers, transformed);
+        }
+    }
+
+    #[test]
+    fn decreasing_numbers_work() {
+        let numbers = vec![10, 4, 7, 0];
+
+        let transforms = [Transform::Delta, Transform::ZigZag];
+
+        let bits = encode_transformed::<FibbonaciEncoder>(&numbers, &transforms).unwrap();
+
+        assert_eq!(numbers, decode_transformed::<FibbonaciDecoder>(&bits));
+
+        // Delta of 1 and 0 wraps around to the biggest number, which can't be shifted.
+        let wrapped = encode_transformed::<EliasGammaEncoder>(&[1, 0], &[Transform::Delta]);
+
+        assert_eq!(io::ErrorKind::InvalidInput, wrapped.err().unwrap().kind());
+
+        let bits = encode_transformed::<EliasGammaEncoder>(&[1, 0], &transforms).unwrap();
+
+        assert_eq!(vec![1, 0], decode_transformed::<EliasGammaDecoder>(&bits));
+    }
+
+    #[test]
+    fn timestamps_work() {
+        let timestamps: Vec<_> = (0..1000).map(|i| 1_700_000_000 + 60 * i + i % 3).collect();
+        let transforms = [
+            Transform::FrameOfReference,
+            Transform::DeltaOfDelta,
+            Transform::ZigZag,
+        ];
+
+        let transformed = encode_transformed::<EliasGammaEncoder>(&timestamps, &transforms).unwrap();
+        let plain = encode_transformed::<EliasGammaEncoder>(&timestamps, &[]).unwrap();
+
+        assert_eq!(timestamps, decode_transformed::<EliasGammaDecoder>(&transformed));
+        assert_eq!(timestamps, decode_transformed::<EliasGammaDecoder>(&plain));
+        assert!(transformed.len() * 10 < plain.len());
+    }
+
+    #[test]
+    fn transforms_kept_elsewhere_work() {
+        let numbers = vec![1000, 1010, 1005, 1020];
+        let transforms = [Transform::FrameOfReference, Transform::Xor];
+
+        let bits = encode_with_transforms(&numbers, &transforms, EliasGammaEncoder::encode).unwrap();
+
+        assert_eq!(
+            numbers,
+            decode_with_transforms(&bits, &transforms, EliasGammaDecoder::decode)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "too many transforms")]
+    fn too_many_transforms_panic() {
+        let _ = encode_transformed::<EliasGammaEncoder>(&[1], &[Transform::Xor; 256]);
+    }
+}